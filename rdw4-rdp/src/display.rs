@@ -16,6 +16,9 @@ use crate::{
     util::{format_from_mime, string_from_utf16, utf16_from_utf8},
 };
 
+// the touchpad scroll distance, in surface units, of a wheel click
+const SURFACE_UNITS_PER_CLICK: f64 = 10.0;
+
 #[repr(C)]
 pub struct RdwRdpDisplay {
     parent: rdw::RdwDisplay,
@@ -103,6 +106,8 @@ mod imp {
         keymap: Cell<Option<&'static [u16]>>,
         connected: Cell<bool>,
         eodl_tx: RefCell<Option<oneshot::Sender<()>>>,
+        // the resize-requests are aggregated by a monitor manager
+        pub(crate) monitors: RefCell<rdw::WeakMonitorManager>,
     }
 
     impl Default for Display {
@@ -128,6 +133,7 @@ mod imp {
                 keymap: Default::default(),
                 connected: Default::default(),
                 eodl_tx: Default::default(),
+                monitors: Default::default(),
             }
         }
     }
@@ -230,23 +236,41 @@ mod imp {
                     }));
                 }));
 
-            self.obj().connect_resize_request(
-                clone!(@weak self as this => move |_, width, height, wmm, hmm| {
-                    let scale_factor = this.obj().scale_factor() * 100;
-                    log::debug!("resize-request: {:?}", (width, height, wmm, hmm, scale_factor));
+            self.obj().connect_scroll_smooth(
+                clone!(@weak self as this => move |_, dx, dy, v120_x, v120_y| {
+                    log::debug!("scroll-smooth: {:?}", (dx, dy, v120_x, v120_y));
+                    let (v120_x, v120_y) = if (v120_x, v120_y) == (0, 0) {
+                        // a touchpad, in surface units
+                        let v120 = |d: f64| (d * 120.0 / SURFACE_UNITS_PER_CLICK).round() as i32;
+                        (v120(dx), v120(dy))
+                    } else {
+                        (v120_x, v120_y)
+                    };
                     MainContext::default().spawn_local(glib::clone!(@weak this => async move {
-                        let _ = this.send_event(Event::MonitorLayout(vec![MonitorLayout::new(
-                            MonitorFlags::PRIMARY,
-                            0, 0,
-                            width, height,
-                            wmm, hmm,
-                            Orientation::Landscape,
-                            scale_factor as _,
-                            100,
-                        )])).await;
+                        let _ = this.mouse_scroll(PtrFlags::HWHEEL, v120_x).await;
+                        let _ = this.mouse_scroll(PtrFlags::WHEEL, v120_y).await;
                     }));
                 }),
             );
+
+            self.obj().connect_resize_request(
+                clone!(@weak self as this => move |_, width, height, wmm, hmm, scale| {
+                    log::debug!("resize-request: {:?}", (width, height, wmm, hmm, scale));
+                    if this.monitors.borrow().upgrade().is_some() {
+                        // sent along the other monitors
+                        return;
+                    }
+                    this.send_monitor_layout(&[rdw::MonitorLayout {
+                        width,
+                        height,
+                        width_mm: wmm,
+                        height_mm: hmm,
+                        primary: true,
+                        scale,
+                        ..Default::default()
+                    }]);
+                }),
+            );
         }
     }
 
@@ -256,18 +280,6 @@ mod imp {
 
             self.keymap.set(rdw::keymap_xtkbd());
 
-            let ec = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::BOTH_AXES);
-            self.obj().add_controller(&ec);
-            ec.connect_scroll(
-                clone!(@weak self as this => @default-panic, move |_, dx, dy| {
-                    MainContext::default().spawn_local(glib::clone!(@weak this => async move {
-                        let _ = this.mouse_scroll(PtrFlags::HWHEEL, dx).await;
-                        let _ = this.mouse_scroll(PtrFlags::WHEEL, dy).await;
-                    }));
-                    glib::signal::Inhibit(false)
-                }),
-            );
-
             let cb = gdk::traits::DisplayExt::clipboard(&self.obj().display());
             let watch_id = cb.connect_changed(clone!(@weak self as this => move |clipboard| {
                 let is_local = clipboard.is_local();
@@ -553,6 +565,40 @@ mod imp {
             res
         }
 
+        pub(crate) fn send_monitor_layout(&self, layout: &[rdw::MonitorLayout]) {
+            let layout = layout
+                .iter()
+                .map(|m| {
+                    // the desktop scale is a percentage, the device scale one of 100, 140 or 180
+                    let desktop_scale = ((m.scale * 100.0).round() as u32).clamp(100, 500);
+                    let device_scale = [100, 140, 180]
+                        .into_iter()
+                        .min_by_key(|s| (*s as i32 - desktop_scale as i32).abs())
+                        .unwrap();
+                    let flags = if m.primary {
+                        MonitorFlags::PRIMARY
+                    } else {
+                        MonitorFlags::empty()
+                    };
+                    MonitorLayout::new(
+                        flags,
+                        m.x,
+                        m.y,
+                        m.width,
+                        m.height,
+                        m.width_mm,
+                        m.height_mm,
+                        Orientation::Landscape,
+                        desktop_scale,
+                        device_scale,
+                    )
+                })
+                .collect();
+            MainContext::default().spawn_local(glib::clone!(@weak self as this => async move {
+                let _ = this.send_event(Event::MonitorLayout(layout)).await;
+            }));
+        }
+
         async fn send_event(&self, event: Event) -> Result<()> {
             match &*self.tx.borrow() {
                 Some(tx) => {
@@ -564,17 +610,17 @@ mod imp {
             }
         }
 
-        async fn mouse_click(&self, press: bool, button: u32) -> Result<()> {
+        async fn mouse_click(&self, press: bool, button: rdw::MouseButton) -> Result<()> {
             let (x, y) = self.last_mouse.get();
             let (x, y) = (x as _, y as _);
             let mut event = match button {
-                gdk::BUTTON_PRIMARY => Event::Mouse(PtrFlags::BUTTON1, x, y),
-                gdk::BUTTON_MIDDLE => Event::Mouse(PtrFlags::BUTTON3, x, y),
-                gdk::BUTTON_SECONDARY => Event::Mouse(PtrFlags::BUTTON2, x, y),
-                8 | 97 => Event::XMouse(PtrXFlags::BUTTON1, x, y),
-                9 | 112 => Event::XMouse(PtrXFlags::BUTTON2, x, y),
+                rdw::MouseButton::Left => Event::Mouse(PtrFlags::BUTTON1, x, y),
+                rdw::MouseButton::Middle => Event::Mouse(PtrFlags::BUTTON3, x, y),
+                rdw::MouseButton::Right => Event::Mouse(PtrFlags::BUTTON2, x, y),
+                rdw::MouseButton::Back => Event::XMouse(PtrXFlags::BUTTON1, x, y),
+                rdw::MouseButton::Forward => Event::XMouse(PtrXFlags::BUTTON2, x, y),
                 _ => {
-                    return Err(RdpError::Failed(format!("Unhandled button {}", button)));
+                    return Err(RdpError::Failed(format!("Unhandled button {:?}", button)));
                 }
             };
             if press {
@@ -591,9 +637,12 @@ mod imp {
             self.send_event(event).await
         }
 
-        async fn mouse_scroll(&self, flags: PtrFlags, delta: f64) -> Result<()> {
+        async fn mouse_scroll(&self, flags: PtrFlags, v120: i32) -> Result<()> {
+            if v120 == 0 {
+                return Ok(());
+            }
             // FIXME: loop for large values?
-            let windows_delta = f64::clamp(delta * -120.0, -256.0, 255.0) as i16;
+            let windows_delta = i32::clamp(-v120, -256, 255) as i16;
             self.send_event(Event::Mouse(
                 unsafe {
                     PtrFlags::from_bits_unchecked(
@@ -712,7 +761,7 @@ mod imp {
 }
 
 glib::wrapper! {
-    pub struct Display(ObjectSubclass<imp::Display>) @extends rdw::Display, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+    pub struct Display(ObjectSubclass<imp::Display>) @extends rdw::Display, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
 }
 
 impl Display {
@@ -735,6 +784,16 @@ impl Display {
         self.imp().disconnect().await
     }
 
+    /// The manager of the session monitors, this display being the primary one.
+    ///
+    /// The RDP desktop is a single surface, no display is spawned for other
+    /// heads, but the layout of the managed displays is sent as one message.
+    pub fn monitor_manager(&self) -> rdw::MonitorManager {
+        rdw::MonitorManager::for_display(self, &self.imp().monitors, |this, layout| {
+            this.imp().send_monitor_layout(layout);
+        })
+    }
+
     pub fn last_error(&self) -> Option<RdpErr> {
         let ctxt = self.imp().context.lock().unwrap();
         ctxt.last_error()
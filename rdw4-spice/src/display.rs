@@ -12,7 +12,7 @@ mod imp {
     use super::*;
     use crate::util;
     use gtk::subclass::prelude::*;
-    use once_cell::sync::Lazy;
+    use once_cell::{sync::Lazy, unsync::OnceCell};
     use std::cell::{Cell, RefCell};
 
     #[repr(C)]
@@ -55,13 +55,19 @@ mod imp {
     #[derive(Default)]
     pub struct Display {
         pub(crate) keymap: Cell<Option<&'static [u16]>>,
-        pub(crate) session: spice::Session,
+        // shared by the displays of the session monitors
+        pub(crate) session: OnceCell<spice::Session>,
         pub(crate) monitor_config: Cell<Option<spice::DisplayMonitorConfig>>,
         pub(crate) main: glib::WeakRef<spice::MainChannel>,
         pub(crate) input: glib::WeakRef<spice::InputsChannel>,
         pub(crate) display: glib::WeakRef<spice::DisplayChannel>,
         pub(crate) last_button_state: Cell<Option<i32>>,
-        pub(crate) nth_monitor: usize,
+        // a gl-draw is waiting for the scanout to be released
+        pub(crate) gl_draw_pending: Cell<bool>,
+        pub(crate) nth_monitor: Cell<usize>,
+        // the resize-requests are aggregated by the manager,
+        // the primary monitor follows the guest heads
+        pub(crate) monitors: RefCell<rdw::WeakMonitorManager>,
         pub(crate) clipboard: [Clipboard; 2],
     }
 
@@ -79,27 +85,47 @@ mod imp {
             use glib::ParamFlags as Flags;
 
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-                vec![glib::ParamSpecObject::new(
-                    "session",
-                    "Session",
-                    "Spice client session",
-                    spice::Session::static_type(),
-                    Flags::READABLE,
-                )]
+                vec![
+                    glib::ParamSpecObject::new(
+                        "session",
+                        "Session",
+                        "Spice client session",
+                        spice::Session::static_type(),
+                        Flags::READWRITE | Flags::CONSTRUCT_ONLY,
+                    ),
+                    glib::ParamSpecUInt::new(
+                        "nth-monitor",
+                        "Nth monitor",
+                        "The guest monitor shown",
+                        0,
+                        u32::MAX,
+                        0,
+                        Flags::READWRITE | Flags::CONSTRUCT_ONLY,
+                    ),
+                ]
             });
             PROPERTIES.as_ref()
         }
 
-        fn set_property(&self, _id: usize, _value: &glib::Value, pspec: &glib::ParamSpec) {
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
             match pspec.name() {
-                "session" => panic!(),
+                "session" => {
+                    if let Some(session) = value.get::<Option<spice::Session>>().unwrap() {
+                        self.session.set(session).unwrap();
+                    }
+                }
+                "nth-monitor" => {
+                    let nth: u32 = value.get().unwrap();
+                    self.nth_monitor.set(nth as _);
+                }
                 _ => unimplemented!(),
             }
         }
 
         fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             match pspec.name() {
-                "session" => self.session.to_value(),
+                "session" => self.session().to_value(),
+                "nth-monitor" => (self.nth_monitor.get() as u32).to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -129,7 +155,7 @@ mod imp {
             self.obj().connect_motion(clone!(@weak self as this => move |_, x, y| {
                 log::debug!("motion: {:?}", (x, y));
                 if let Some(input) = this.input.upgrade() {
-                    input.position(x as _, y as _, this.nth_monitor as _, this.last_button_state());
+                    input.position(x as _, y as _, this.nth_monitor.get() as _, this.last_button_state());
                 }
             }));
 
@@ -159,286 +185,363 @@ mod imp {
                     this.scroll(scroll);
                 }));
 
-            self.obj().connect_resize_request(clone!(@weak self as this => move |_, width, height, wmm, hmm| {
-                log::debug!("resize-request: {:?}", (width, height));
-                if let Some(main) = this.main.upgrade() {
-                    main.update_display_enabled(this.nth_monitor as _, true, false);
-                    main.update_display_mm(this.nth_monitor as _, wmm as _, hmm as _, false);
-                    main.update_display(this.nth_monitor as _, 0, 0, width as _, height as _, true);
-                }
+            self.obj().connect_resize_request(
+                // spice has no notion of scale, the size is in device pixels
+                clone!(@weak self as this => move |_, width, height, wmm, hmm, scale| {
+                    log::debug!("resize-request: {:?}", (width, height, scale));
+                    if this.monitors.borrow().upgrade().is_some() {
+                        // sent along the other monitors
+                        return;
+                    }
+                    let nth = this.nth_monitor.get() as _;
+                    if let Some(main) = this.main.upgrade() {
+                        main.update_display_enabled(nth, true, false);
+                        main.update_display_mm(nth, wmm as _, hmm as _, false);
+                        main.update_display(nth, 0, 0, width as _, height as _, true);
+                    }
+                }),
+            );
+
+            let session = self.session.get_or_init(Default::default);
+            session.connect_channel_new(clone!(@weak self as this => move |_session, channel| {
+                this.channel_new(channel);
             }));
+            // a secondary monitor may be created once the channels are up
+            for channel in session.channels() {
+                self.channel_new(&channel);
+            }
+        }
 
-            let session = &self.session;
+        fn dispose(&self) {
+            if let Some(id) = self.clipboard[0].watch_id.take() {
+                let clipboard = self.clipboard_from_selection(0).unwrap();
+                clipboard.disconnect(id);
+            }
+            if let Some(id) = self.clipboard[1].watch_id.take() {
+                let clipboard = self.clipboard_from_selection(1).unwrap();
+                clipboard.disconnect(id);
+            }
+        }
+    }
 
-            session.connect_channel_new(clone!(@weak self as this => move |_session, channel| {
-                use spice::ChannelType::*;
+    impl WidgetImpl for Display {
+        fn realize(&self) {
+            self.parent_realize();
 
-                let type_ = match spice::ChannelType::try_from(channel.channel_type()) {
-                    Ok(t) => t,
-                    _ => return,
-                };
+            if self.is_primary() {
+                self.add_clipboard_watch(0);
+                self.add_clipboard_watch(1);
+            }
+            self.keymap.set(rdw::keymap_xtkbd());
+        }
+    }
 
-                match type_ {
-                    Main => {
-                        let main = channel.clone().downcast::<spice::MainChannel>().unwrap();
-                        this.main.set(Some(&main));
+    impl rdw::DisplayImpl for Display {}
 
-                        main.connect_channel_event(clone!(@weak this => move |_, event| {
-                            use spice::ChannelEvent::*;
+    impl Display {
+        fn channel_new(&self, channel: &spice::Channel) {
+            use spice::ChannelType::*;
 
-                            if event == Closed {
-                                this.session.disconnect();
-                            }
-                        }));
+            let type_ = match spice::ChannelType::try_from(channel.channel_type()) {
+                Ok(t) => t,
+                _ => return,
+            };
 
-                        main.connect_main_mouse_update(clone!(@weak this => move |main| {
-                            let mode = spice::MouseMode::from_bits_truncate(main.mouse_mode());
-                            log::debug!("mouse-update: {:?}", mode);
-                            this.obj().set_mouse_absolute(mode.contains(spice::MouseMode::CLIENT));
-                        }));
+            match type_ {
+                Main => {
+                    let main = channel.clone().downcast::<spice::MainChannel>().unwrap();
+                    self.main.set(Some(&main));
+
+                    main.connect_main_mouse_update(clone!(@weak self as this => move |main| {
+                        let mode = spice::MouseMode::from_bits_truncate(main.mouse_mode());
+                        log::debug!("mouse-update: {:?}", mode);
+                        this.obj().set_mouse_absolute(mode.contains(spice::MouseMode::CLIENT));
+                    }));
+
+                    // the guest is resized by the agent
+                    main.connect_notify_local(
+                        Some("agent-connected"),
+                        clone!(@weak self as this => move |main, _| {
+                            let connected = main.property::<bool>("agent-connected");
+                            log::debug!("agent-connected: {}", connected);
+                            this.obj().set_resize_supported(connected);
+                        }),
+                    );
+                    self.obj()
+                        .set_resize_supported(main.property::<bool>("agent-connected"));
 
-                        main.connect_main_clipboard_selection(clone!(@weak this => move |_main, selection, type_, data| {
-                            log::debug!("clipboard-data: {:?}", (selection, type_, data.len()));
-                            if let Some((req_type, mut tx)) = this.clipboard[selection as usize].tx.take() {
-                                if type_ != req_type as u32 {
-                                    log::warn!("Didn't get expected type from guest clipboard!");
-                                    return;
-                                }
-                                if let Err(e) = tx.try_send(glib::Bytes::from(data)) {
-                                    log::warn!("Failed to send clipboard data to future: {}", e);
-                                }
-                            }
-                        }));
+                    // the session and clipboard are handled by the primary monitor
+                    if !self.is_primary() {
+                        return;
+                    }
 
-                        main.connect_main_clipboard_selection_grab(clone!(@weak this => move |_main, selection, types| {
-                            let types: Vec<_> = types.iter()
-                                                     .filter_map(|&t| spice::ClipboardFormat::try_from(t as i32).ok())
-                                                     .filter_map(util::mime_from_format)
-                                                     .collect();
-                            log::debug!("clipboard-grab: {:?}", (selection, &types));
-                            if let Some(clipboard) = this.clipboard_from_selection(selection) {
-                                let content = rdw::ContentProvider::new(&types, clone!(@weak this => @default-return None, move |mime, stream, prio| {
-                                    log::debug!("content-provider-write: {:?}", (mime, stream));
-                                    let format = match util::format_from_mime(mime) {
-                                        Some(f) => f,
-                                        None => return None,
-                                    };
-
-                                    Some(Box::pin(clone!(@weak this, @strong stream => @default-return panic!(), async move {
-                                        use futures::stream::StreamExt;
-
-                                        if this.clipboard[selection as usize].tx.borrow().is_some() {
-                                            return Err(glib::Error::new(gio::IOErrorEnum::Failed, "clipboard request pending"));
-                                        }
+                    main.connect_channel_event(clone!(@weak self as this => move |_, event| {
+                        use spice::ChannelEvent::*;
 
-                                        if let Some(main) = this.main.upgrade() {
-                                            let (tx, mut rx) = futures::channel::mpsc::channel(1);
-                                            this.clipboard[selection as usize].tx.replace(Some((format, tx)));
-                                            main.clipboard_selection_request(selection, format as u32);
-                                            if let Some(bytes) = rx.next().await {
-                                                return stream.write_bytes_future(&bytes, prio).await.map(|_| ());
-                                            }
+                        if event == Closed {
+                            this.session().disconnect();
+                        }
+                    }));
+
+                    main.connect_main_clipboard_selection(clone!(@weak self as this => move |_main, selection, type_, data| {
+                        log::debug!("clipboard-data: {:?}", (selection, type_, data.len()));
+                        if let Some((req_type, mut tx)) = this.clipboard[selection as usize].tx.take() {
+                            if type_ != req_type as u32 {
+                                log::warn!("Didn't get expected type from guest clipboard!");
+                                return;
+                            }
+                            if let Err(e) = tx.try_send(glib::Bytes::from(data)) {
+                                log::warn!("Failed to send clipboard data to future: {}", e);
+                            }
+                        }
+                    }));
+
+                    main.connect_main_clipboard_selection_grab(clone!(@weak self as this => move |_main, selection, types| {
+                        let types: Vec<_> = types.iter()
+                                                 .filter_map(|&t| spice::ClipboardFormat::try_from(t as i32).ok())
+                                                 .filter_map(util::mime_from_format)
+                                                 .collect();
+                        log::debug!("clipboard-grab: {:?}", (selection, &types));
+                        if let Some(clipboard) = this.clipboard_from_selection(selection) {
+                            let content = rdw::ContentProvider::new(&types, clone!(@weak this => @default-return None, move |mime, stream, prio| {
+                                log::debug!("content-provider-write: {:?}", (mime, stream));
+                                let format = match util::format_from_mime(mime) {
+                                    Some(f) => f,
+                                    None => return None,
+                                };
+
+                                Some(Box::pin(clone!(@weak this, @strong stream => @default-return panic!(), async move {
+                                    use futures::stream::StreamExt;
+
+                                    if this.clipboard[selection as usize].tx.borrow().is_some() {
+                                        return Err(glib::Error::new(gio::IOErrorEnum::Failed, "clipboard request pending"));
+                                    }
+
+                                    if let Some(main) = this.main.upgrade() {
+                                        let (tx, mut rx) = futures::channel::mpsc::channel(1);
+                                        this.clipboard[selection as usize].tx.replace(Some((format, tx)));
+                                        main.clipboard_selection_request(selection, format as u32);
+                                        if let Some(bytes) = rx.next().await {
+                                            return stream.write_bytes_future(&bytes, prio).await.map(|_| ());
                                         }
+                                    }
 
-                                        Err(glib::Error::new(gio::IOErrorEnum::Failed, "failed to request clipboard data"))
-                                    })))
-                                }));
-                                if let Err(e) = clipboard.set_content(Some(&content)) {
-                                    log::warn!("Failed to set clipboard grab: {}", e);
-                                }
+                                    Err(glib::Error::new(gio::IOErrorEnum::Failed, "failed to request clipboard data"))
+                                })))
+                            }));
+                            if let Err(e) = clipboard.set_content(Some(&content)) {
+                                log::warn!("Failed to set clipboard grab: {}", e);
                             }
-                        }));
+                        }
+                    }));
 
-                        main.connect_main_clipboard_selection_release(clone!(@weak this => move |_main, selection| {
+                    main.connect_main_clipboard_selection_release(
+                        clone!(@weak self as this => move |_main, selection| {
                             log::debug!("clipboard-release: {:?}", selection);
                             if let Some(clipboard) = this.clipboard_from_selection(selection) {
                                 if let Err(e) = clipboard.set_content(gdk::ContentProvider::NONE) {
                                     log::warn!("Failed to release clipboard: {}", e);
                                 }
                             }
-                        }));
+                        }),
+                    );
 
-                        main.connect_main_clipboard_selection_request(clone!(@weak this => @default-return false, move |main, selection, type_| {
-                            let mime = spice::ClipboardFormat::try_from(type_ as i32).map_or(None, util::mime_from_format);
-                            log::debug!("clipboard-request: {:?}", (selection, mime));
-
-                            if let (Some(mime), Some(clipboard)) = (mime, this.clipboard_from_selection(selection)) {
-                                glib::MainContext::default().spawn_local(glib::clone!(@weak this, @weak clipboard, @strong main => async move {
-                                    let res = clipboard.read_future(&[mime], glib::Priority::default()).await;
-                                    log::debug!("clipboard-read: {:?}", res);
-
-                                    if let Ok((stream, mime)) = res {
-                                        if let Some(format) = util::format_from_mime(&mime) {
-                                            let out = gio::MemoryOutputStream::new_resizable();
-                                            let res = out.splice_future(
-                                                &stream,
-                                                gio::OutputStreamSpliceFlags::CLOSE_SOURCE | gio::OutputStreamSpliceFlags::CLOSE_TARGET,
-                                                glib::Priority::default()).await;
-                                            match res {
-                                                Ok(size) => {
-                                                    let data = out.steal_as_bytes();
-                                                    main.clipboard_selection_notify(selection, format as u32, data.as_ref());
-                                                    log::debug!("clipboard-sent: {}", size);
-                                                    return;
-                                                }
-                                                Err(e) => {
-                                                    log::warn!("Failed to read clipboard: {}", e);
-                                                }
+                    main.connect_main_clipboard_selection_request(clone!(@weak self as this => @default-return false, move |main, selection, type_| {
+                        let mime = spice::ClipboardFormat::try_from(type_ as i32).map_or(None, util::mime_from_format);
+                        log::debug!("clipboard-request: {:?}", (selection, mime));
+
+                        if let (Some(mime), Some(clipboard)) = (mime, this.clipboard_from_selection(selection)) {
+                            glib::MainContext::default().spawn_local(glib::clone!(@weak this, @weak clipboard, @strong main => async move {
+                                let res = clipboard.read_future(&[mime], glib::Priority::default()).await;
+                                log::debug!("clipboard-read: {:?}", res);
+
+                                if let Ok((stream, mime)) = res {
+                                    if let Some(format) = util::format_from_mime(&mime) {
+                                        let out = gio::MemoryOutputStream::new_resizable();
+                                        let res = out.splice_future(
+                                            &stream,
+                                            gio::OutputStreamSpliceFlags::CLOSE_SOURCE | gio::OutputStreamSpliceFlags::CLOSE_TARGET,
+                                            glib::Priority::default()).await;
+                                        match res {
+                                            Ok(size) => {
+                                                let data = out.steal_as_bytes();
+                                                main.clipboard_selection_notify(selection, format as u32, data.as_ref());
+                                                log::debug!("clipboard-sent: {}", size);
+                                                return;
+                                            }
+                                            Err(e) => {
+                                                log::warn!("Failed to read clipboard: {}", e);
                                             }
                                         }
                                     }
-                                    main.clipboard_selection_notify(selection, 0, &[]);
-                                }));
-                            }
-                            true
-                        }));
-                    },
-                    Inputs => {
-                        let input = channel.clone().downcast::<spice::InputsChannel>().unwrap();
-                        this.input.set(Some(&input));
-
-                        input.connect_inputs_modifiers(clone!(@weak this => move |input| {
-                            let modifiers = input.key_modifiers();
-                            log::debug!("inputs-modifiers: {}", modifiers);
-                            input.connect_channel_event(clone!(@weak this => move |input, event| {
-                                if event == spice::ChannelEvent::Opened && input.socket().unwrap().family() == gio::SocketFamily::Unix {
-                                    log::debug!("on unix socket");
                                 }
+                                main.clipboard_selection_notify(selection, 0, &[]);
                             }));
-                        }));
-                        ChannelExt::connect(&input);
+                        }
+                        true
+                    }));
+                }
+                Inputs => {
+                    let input = channel.clone().downcast::<spice::InputsChannel>().unwrap();
+                    self.input.set(Some(&input));
+                    if !self.is_primary() {
+                        return;
                     }
-                    Display => {
-                        let dpy = channel.clone().downcast::<spice::DisplayChannel>().unwrap();
-                        this.display.set(Some(&dpy));
 
-                        dpy.connect_display_primary_create(clone!(@weak this => move |_| {
-                            log::debug!("primary-create");
+                    input.connect_inputs_modifiers(clone!(@weak self as this => move |input| {
+                        let modifiers = input.key_modifiers();
+                        log::debug!("inputs-modifiers: {}", modifiers);
+                        input.connect_channel_event(clone!(@weak this => move |input, event| {
+                            if event == spice::ChannelEvent::Opened && input.socket().unwrap().family() == gio::SocketFamily::Unix {
+                                log::debug!("on unix socket");
+                            }
                         }));
+                    }));
+                    ChannelExt::connect(&input);
+                }
+                Display => {
+                    let dpy = channel.clone().downcast::<spice::DisplayChannel>().unwrap();
+                    self.display.set(Some(&dpy));
 
-                        dpy.connect_display_primary_destroy(|_| {
-                            log::debug!("primary-destroy");
-                        });
+                    dpy.connect_display_primary_create(clone!(@weak self as this => move |_| {
+                        log::debug!("primary-create");
+                    }));
 
-                        dpy.connect_display_mark(clone!(@weak this => move |_, mark| {
-                            log::debug!("primary-mark: {}", mark);
-                            this.invalidate_monitor();
-                        }));
+                    dpy.connect_display_primary_destroy(|_| {
+                        log::debug!("primary-destroy");
+                    });
 
-                        dpy.connect_display_invalidate(clone!(@weak this => move |_, x, y, w, h| {
+                    dpy.connect_display_mark(clone!(@weak self as this => move |_, mark| {
+                        log::debug!("primary-mark: {}", mark);
+                        this.invalidate_monitor();
+                    }));
+
+                    dpy.connect_display_invalidate(
+                        clone!(@weak self as this => move |_, x, y, w, h| {
                             log::debug!("primary-invalidate: {:?}", (x, y, w, h));
                             this.invalidate(x as _, y as _, w as _, h as _);
-                        }));
+                        }),
+                    );
 
-                        dpy.connect_gl_scanout_notify(clone!(@weak this => move |dpy| {
-                            let scanout = dpy.gl_scanout();
-                            log::debug!("notify::gl-scanout: {:?}", scanout);
-
-                            #[cfg(unix)]
-                            if let Some(scanout) = scanout {
-                                this.obj().set_dmabuf_scanout(rdw::RdwDmabufScanout {
-                                    width: scanout.width(),
-                                    height: scanout.height(),
-                                    stride: scanout.stride(),
-                                    fourcc: scanout.format(),
-                                    y0_top: scanout.y0_top(),
-                                    modifier: 0,
-                                    fd: scanout.into_raw_fd(),
-                                });
+                    dpy.connect_gl_scanout_notify(clone!(@weak self as this => move |dpy| {
+                        log::debug!("notify::gl-scanout");
+                        #[cfg(unix)]
+                        this.gl_scanout_changed(dpy);
+                    }));
+
+                    dpy.connect_gl_draw(clone!(@weak self as this => move |_, x, y, w, h| {
+                        log::debug!("gl-draw: {:?}", (x, y, w, h));
+                        let (x, y, w, h) = (x as usize, y as usize, w as usize, h as usize);
+                        let damage = match this.monitor_config.get() {
+                            Some(config) => {
+                                let (monitor_x, monitor_y, _, _) = config.geometry();
+                                this.monitor_clip(x, y, w, h)
+                                    .map(|(x, y, w, h)| (x - monitor_x, y - monitor_y, w, h))
                             }
-                        }));
-
-                        dpy.connect_gl_draw(clone!(@weak this => move |dpy, x, y, w, h| {
-                            log::debug!("gl-draw: {:?}", (x, y, w, h));
-                            this.obj().render();
-                            dpy.gl_draw_done();
-                        }));
-
-                        dpy.connect_monitors_notify(clone!(@weak this => move |dpy| {
-                            let monitors = dpy.monitors();
-                            log::debug!("notify::monitors: {:?}", monitors);
-
-                            let monitor_config = monitors.and_then(|m| m.get(this.nth_monitor).copied());
-                            if let Some((0, 0, w, h)) = monitor_config.map(|c| c.geometry()) {
-                                this.obj().set_display_size(Some((w, h)));
+                            None => Some((x, y, w, h)),
+                        };
+                        match damage {
+                            Some((x, y, w, h)) => {
+                                this.obj().add_damage(x as _, y as _, w as _, h as _)
                             }
-                            this.monitor_config.set(monitor_config);
-                        }));
-
-                        ChannelExt::connect(&dpy);
-                    },
-                    Cursor => {
-                        let cursor = channel.clone().downcast::<spice::CursorChannel>().unwrap();
-
-                        cursor.connect_cursor_move(clone!(@weak this => move |_cursor, x, y| {
-                            log::debug!("cursor-move: {:?}", (x, y));
-                            this.obj().set_cursor_position(Some((x as _, y as _)));
-                        }));
-
-                        cursor.connect_cursor_reset(clone!(@weak this => move |_cursor| {
-                            log::debug!("cursor-reset");
-                            this.obj().define_cursor(None);
-                        }));
+                            // another head was drawn
+                            None if !this.is_primary() => return,
+                            None => (),
+                        }
+                        // the displays share the scanout, the primary sends the single gl-draw-done
+                        if this.is_primary() {
+                            this.gl_draw_pending.set(true);
+                        }
+                        this.obj().render();
+                    }));
+
+                    #[cfg(unix)]
+                    self.obj().connect_dmabuf_released(
+                        clone!(@weak dpy, @weak self as this => move |_| {
+                            if this.gl_draw_pending.replace(false) {
+                                dpy.gl_draw_done();
+                            }
+                        }),
+                    );
 
-                        cursor.connect_cursor_hide(clone!(@weak this => move |_cursor| {
-                            log::debug!("cursor-hide");
-                            let cursor = gdk::Cursor::from_name("none", None);
-                            this.obj().define_cursor(cursor);
-                        }));
+                    dpy.connect_monitors_notify(clone!(@weak self as this => move |dpy| {
+                        this.monitors_changed(dpy);
+                    }));
 
-                        cursor.connect_cursor_notify(clone!(@weak this => move |cursor| {
-                            let cursor = cursor.cursor();
-                            log::debug!("cursor-notify: {:?}", cursor);
-                            if let Some(cursor) = cursor {
-                                match cursor.cursor_type() {
-                                    Ok(spice::CursorType::Alpha) => {
-                                        let cursor = rdw::Display::make_cursor(
-                                            cursor.data().unwrap(),
-                                            cursor.width(),
-                                            cursor.height(),
-                                            0,
-                                            0,
-                                            1,
-                                        );
-                                        this.obj().define_cursor(Some(cursor));
+                    if self.is_primary() {
+                        ChannelExt::connect(&dpy);
+                    } else {
+                        self.monitors_changed(&dpy);
+                    }
+                }
+                Cursor => {
+                    let cursor = channel.clone().downcast::<spice::CursorChannel>().unwrap();
+
+                    cursor.connect_cursor_move(clone!(@weak self as this => move |_cursor, x, y| {
+                        log::debug!("cursor-move: {:?}", (x, y));
+                        this.obj().set_cursor_position(this.monitor_pos(x, y));
+                    }));
+
+                    cursor.connect_cursor_reset(clone!(@weak self as this => move |_cursor| {
+                        log::debug!("cursor-reset");
+                        this.obj().define_cursor(None);
+                    }));
+
+                    cursor.connect_cursor_hide(clone!(@weak self as this => move |_cursor| {
+                        log::debug!("cursor-hide");
+                        let cursor = gdk::Cursor::from_name("none", None);
+                        this.obj().define_cursor(cursor);
+                    }));
+
+                    cursor.connect_cursor_notify(clone!(@weak self as this => move |cursor| {
+                        let cursor = cursor.cursor();
+                        log::debug!("cursor-notify: {:?}", cursor);
+                        if let Some(cursor) = cursor {
+                            match cursor.cursor_type() {
+                                Ok(spice::CursorType::Alpha) => {
+                                    let cursor = rdw::Display::make_cursor(
+                                        cursor.data().unwrap(),
+                                        cursor.width(),
+                                        cursor.height(),
+                                        cursor.hot_spot_x(),
+                                        cursor.hot_spot_y(),
+                                        1,
+                                    );
+                                    this.obj().define_cursor(Some(cursor));
+                                }
+                                Ok(spice::CursorType::Mono) => {
+                                    // the AND mask, followed by the XOR mask
+                                    let data = cursor.data().unwrap();
+                                    let (w, h) = (cursor.width(), cursor.height());
+                                    let len = ((w as usize + 7) / 8) * h as usize;
+                                    if data.len() < len * 2 {
+                                        log::warn!("Invalid mono cursor data");
+                                        return;
                                     }
-                                    e => log::warn!("Unhandled cursor type: {:?}", e),
+                                    let cursor = rdw::Display::make_mono_cursor(
+                                        &data[..len],
+                                        &data[len..len * 2],
+                                        w,
+                                        h,
+                                        cursor.hot_spot_x(),
+                                        cursor.hot_spot_y(),
+                                        1,
+                                    );
+                                    this.obj().define_cursor(Some(cursor));
                                 }
+                                e => log::warn!("Unhandled cursor type: {:?}", e),
                             }
-                        }));
+                        }
+                    }));
 
+                    if self.is_primary() {
                         ChannelExt::connect(&cursor);
                     }
-                    _ => {}
                 }
-            }));
-        }
-
-        fn dispose(&self) {
-            if let Some(id) = self.clipboard[0].watch_id.take() {
-                let clipboard = self.clipboard_from_selection(0).unwrap();
-                clipboard.disconnect(id);
+                _ => {}
             }
-            if let Some(id) = self.clipboard[1].watch_id.take() {
-                let clipboard = self.clipboard_from_selection(1).unwrap();
-                clipboard.disconnect(id);
-            }
-        }
-    }
-
-    impl WidgetImpl for Display {
-        fn realize(&self) {
-            self.parent_realize();
-
-            self.add_clipboard_watch(0);
-            self.add_clipboard_watch(1);
-            self.keymap.set(rdw::keymap_xtkbd());
         }
-    }
 
-    impl rdw::DisplayImpl for Display {}
-
-    impl Display {
         fn add_clipboard_watch(&self, selection: u32) {
             let clipboard = self.clipboard_from_selection(selection).unwrap();
             let watch_id = clipboard.connect_changed(clone!(@weak self as this => move |clipboard| {
@@ -502,13 +605,15 @@ mod imp {
             }
         }
 
-        fn mouse_click(&self, press: bool, button: u32) {
+        fn mouse_click(&self, press: bool, button: rdw::MouseButton) {
             let button = match button {
-                gdk::BUTTON_PRIMARY => spice::MouseButton::Left,
-                gdk::BUTTON_MIDDLE => spice::MouseButton::Middle,
-                gdk::BUTTON_SECONDARY => spice::MouseButton::Right,
+                rdw::MouseButton::Left => spice::MouseButton::Left,
+                rdw::MouseButton::Middle => spice::MouseButton::Middle,
+                rdw::MouseButton::Right => spice::MouseButton::Right,
+                rdw::MouseButton::Back => spice::MouseButton::Side,
+                rdw::MouseButton::Forward => spice::MouseButton::Extra,
                 button => {
-                    log::warn!("Unhandled button event nth: {}", button);
+                    log::warn!("Unhandled button: {:?}", button);
                     return;
                 }
             };
@@ -541,6 +646,127 @@ mod imp {
             })
         }
 
+        pub(crate) fn session(&self) -> &spice::Session {
+            self.session.get().unwrap()
+        }
+
+        fn is_primary(&self) -> bool {
+            self.nth_monitor.get() == 0
+        }
+
+        fn monitors_changed(&self, dpy: &spice::DisplayChannel) {
+            let monitors = dpy.monitors();
+            log::debug!("notify::monitors: {:?}", monitors);
+
+            let monitor_config = monitors
+                .as_ref()
+                .and_then(|m| m.get(self.nth_monitor.get()).copied());
+            if let Some((_, _, w, h)) = monitor_config.map(|c| c.geometry()) {
+                self.obj().set_display_size(Some((w, h)));
+            }
+            self.monitor_config.set(monitor_config);
+            self.invalidate_monitor();
+            #[cfg(unix)]
+            self.gl_scanout_changed(dpy);
+
+            if !self.is_primary() {
+                return;
+            }
+            let manager = self.monitors.borrow().upgrade();
+            if let (Some(manager), Some(monitors)) = (manager, monitors) {
+                let heads: Vec<_> = monitors
+                    .iter()
+                    .map(|c| {
+                        let (x, y, w, h) = c.geometry();
+                        rdw::Rect::new(x as _, y as _, w as _, h as _)
+                    })
+                    .collect();
+                manager.set_heads(&heads);
+            }
+        }
+
+        // import the area of the monitor head
+        #[cfg(unix)]
+        fn gl_scanout_changed(&self, dpy: &spice::DisplayChannel) {
+            let scanout = match dpy.gl_scanout() {
+                Some(scanout) => scanout,
+                _ => return,
+            };
+            log::debug!("gl-scanout: {:?}", scanout);
+
+            let (width, height, stride) = (scanout.width(), scanout.height(), scanout.stride());
+            let (x, y, w, h) = match self.monitor_config.get().map(|c| c.geometry()) {
+                Some((x, y, w, h)) if x + w <= width as usize && y + h <= height as usize => {
+                    (x as u32, y as u32, w as u32, h as u32)
+                }
+                _ => (0, 0, width, height),
+            };
+            let y0_top = scanout.y0_top();
+            let mut s = rdw::RdwDmabufScanout::new(
+                w,
+                h,
+                stride,
+                scanout.format(),
+                0,
+                y0_top,
+                scanout.into_raw_fd(),
+            );
+            // the scanout is linear with 4 bytes per pixel, the head starts at an offset
+            let row = if y0_top { y } else { height - y - h };
+            s.planes[0].offset = row * stride + x * 4;
+            self.obj().set_dmabuf_scanout(s);
+        }
+
+        // clip a guest area to the monitor
+        fn monitor_clip(
+            &self,
+            x: usize,
+            y: usize,
+            w: usize,
+            h: usize,
+        ) -> Option<(usize, usize, usize, usize)> {
+            let (monitor_x, monitor_y, monitor_w, monitor_h) =
+                self.monitor_config.get()?.geometry();
+            let (x0, y0) = (x.max(monitor_x), y.max(monitor_y));
+            let x1 = (x + w).min(monitor_x + monitor_w);
+            let y1 = (y + h).min(monitor_y + monitor_h);
+            (x1 > x0 && y1 > y0).then(|| (x0, y0, x1 - x0, y1 - y0))
+        }
+
+        // guest position -> monitor position
+        fn monitor_pos(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+            let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+            match self.monitor_config.get().map(|c| c.geometry()) {
+                Some((mx, my, mw, mh)) => {
+                    let (x, y) = (x.checked_sub(mx)?, y.checked_sub(my)?);
+                    (x < mw && y < mh).then(|| (x, y))
+                }
+                None => Some((x, y)),
+            }
+        }
+
+        pub(crate) fn send_monitor_layout(&self, layout: &[rdw::MonitorLayout]) {
+            let main = match self.main.upgrade() {
+                Some(main) => main,
+                _ => return,
+            };
+
+            for (i, m) in layout.iter().enumerate() {
+                let nth = m.nth as _;
+                main.update_display_enabled(nth, true, false);
+                main.update_display_mm(nth, m.width_mm as _, m.height_mm as _, false);
+                // send the whole configuration with the last monitor
+                main.update_display(
+                    nth,
+                    m.x as _,
+                    m.y as _,
+                    m.width as _,
+                    m.height as _,
+                    i + 1 == layout.len(),
+                );
+            }
+        }
+
         fn invalidate_monitor(&self) {
             if let Some(c) = self.monitor_config.get() {
                 let (x, y, w, h) = c.geometry();
@@ -549,18 +775,14 @@ mod imp {
         }
 
         fn invalidate(&self, x: usize, y: usize, w: usize, h: usize) {
-            let (monitor_x, monitor_y, _w, _h) = match self.monitor_config.get() {
+            let (monitor_x, monitor_y, _, _) = match self.monitor_config.get() {
                 Some(config) => config.geometry(),
                 _ => return,
             };
-
-            if (monitor_x, monitor_y) != (0, 0) {
-                log::warn!(
-                    "offset monitor geometry not yet supported: {:?}",
-                    (monitor_x, monitor_y)
-                );
-                return;
-            }
+            let (x, y, w, h) = match self.monitor_clip(x, y, w, h) {
+                Some(area) => area,
+                _ => return,
+            };
 
             let primary = match self.primary() {
                 Some(primary) => primary,
@@ -579,14 +801,30 @@ mod imp {
                     let end = (x + w) * 4 + (y + h - 1) * stride;
 
                     self.obj().update_area(
-                        x as _,
-                        y as _,
+                        (x - monitor_x) as _,
+                        (y - monitor_y) as _,
                         w as _,
                         h as _,
                         stride as _,
                         &buf[start..end],
                     );
                 }
+                spice::SurfaceFormat::_16BIT => {
+                    let stride = primary.stride();
+                    let buf = primary.data();
+                    let start = x * 2 + y * stride;
+                    let end = (x + w) * 2 + (y + h - 1) * stride;
+
+                    self.obj().update_area_with_format(
+                        (x - monitor_x) as _,
+                        (y - monitor_y) as _,
+                        w as _,
+                        h as _,
+                        stride as _,
+                        rdw::PixelFormat::Xrgb1555,
+                        &buf[start..end],
+                    );
+                }
                 _ => {
                     log::debug!("format not supported: {:?}", fmt);
                 }
@@ -596,7 +834,7 @@ mod imp {
 }
 
 glib::wrapper! {
-    pub struct Display(ObjectSubclass<imp::Display>) @extends rdw::Display, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+    pub struct Display(ObjectSubclass<imp::Display>) @extends rdw::Display, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
 }
 
 impl Display {
@@ -604,10 +842,45 @@ impl Display {
         glib::Object::new::<Self>(&[])
     }
 
+    /// A display for another monitor of the session.
+    pub fn with_session(session: &spice::Session, nth_monitor: usize) -> Self {
+        glib::Object::new::<Self>(&[("session", session), ("nth-monitor", &(nth_monitor as u32))])
+    }
+
     pub fn session(&self) -> &spice::Session {
         let imp = imp::Display::from_obj(self);
 
-        &imp.session
+        imp.session()
+    }
+
+    /// The manager of the session monitors, this display being the primary one.
+    ///
+    /// Displays are spawned for the other guest heads. The manager isn't kept
+    /// alive by the displays.
+    pub fn monitor_manager(&self) -> rdw::MonitorManager {
+        let imp = imp::Display::from_obj(self);
+        if let Some(manager) = imp.monitors.borrow().upgrade() {
+            return manager;
+        }
+
+        let manager = rdw::MonitorManager::for_display(self, &imp.monitors, |this, layout| {
+            imp::Display::from_obj(this).send_monitor_layout(layout);
+        });
+        let session = self.session().clone();
+        let weak = manager.downgrade();
+        manager.set_display_factory(move |nth| {
+            let display = Display::with_session(&session, nth);
+            imp::Display::from_obj(&display)
+                .monitors
+                .replace(weak.clone());
+            display.upcast()
+        });
+
+        // follow the current guest heads
+        if let Some(dpy) = imp.display.upgrade() {
+            imp.monitors_changed(&dpy);
+        }
+        manager
     }
 }
 
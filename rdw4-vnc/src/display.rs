@@ -50,6 +50,10 @@ mod imp {
         pub(crate) last_motion: Cell<Option<(f64, f64)>>,
         pub(crate) last_button_mask: Cell<Option<u8>>,
         pub(crate) keymap: Cell<Option<&'static [u16]>>,
+        // waiting for the ExtendedDesktopSize reply to SetEncodings
+        pub(crate) probing_resize: Cell<bool>,
+        // set when the desktop size follows the layout of several monitors
+        pub(crate) monitors: RefCell<rdw::WeakMonitorManager>,
     }
 
     impl Default for Display {
@@ -62,6 +66,8 @@ mod imp {
                 last_motion: Cell::new(None),
                 last_button_mask: Cell::new(None),
                 keymap: Cell::new(None),
+                probing_resize: Cell::new(false),
+                monitors: Default::default(),
             }
         }
     }
@@ -167,11 +173,13 @@ mod imp {
                 }));
 
             self.obj().connect_resize_request(
-                clone!(@weak self as this => move |_, width, height, wmm, hmm| {
-                    let sf = this.obj().scale_factor() as u32;
-                    let (width, height) = (width / sf, height / sf);
-                    let status = this.connection.set_size(width, height);
-                    log::debug!("resize-request: {:?} -> {:?}", (width, height, wmm, hmm), status);
+                clone!(@weak self as this => move |_, width, height, wmm, hmm, scale| {
+                    log::debug!("resize-request: {:?}", (width, height, wmm, hmm, scale));
+                    if this.monitors.borrow().upgrade().is_some() {
+                        // sent along the other monitors
+                        return;
+                    }
+                    this.set_size(width, height, scale);
                 }),
             );
 
@@ -235,6 +243,7 @@ mod imp {
             self.connection.connect_vnc_framebuffer_update(
                 clone!(@weak self as this => move |_, x, y, w, h| {
                     log::debug!("framebuffer-update: {:?}", (x, y, w, h));
+                    this.probing_resize.set(false);
                     if let Some(fb) = &*this.fb.borrow() {
                         let sub = fb.get_sub(
                             x as _,
@@ -253,6 +262,10 @@ mod imp {
             self.connection.connect_vnc_desktop_resize(
                 clone!(@weak self as this => move |_, w, h| {
                     log::debug!("desktop-resize: {:?}", (w, h));
+                    if this.probing_resize.replace(false) {
+                        // the server supports ExtendedDesktopResize
+                        this.obj().set_resize_supported(true);
+                    }
                     this.do_framebuffer_init();
                     this.obj().set_display_size(Some((w as _, h as _)));
                     if let Err(e) = this.framebuffer_update_request(false) {
@@ -329,12 +342,26 @@ mod imp {
             }
         }
 
-        fn mouse_click(&self, press: bool, button: u32) {
-            if button > 3 {
-                log::warn!("Unhandled button event nth: {}", button);
-                return;
-            }
-            self.button_event(press, button as _)
+        fn mouse_click(&self, press: bool, button: rdw::MouseButton) {
+            use rdw::MouseButton::*;
+
+            // the RFB button mask has 8 bits, 4 to 7 for the wheel
+            let button = match button {
+                Left => 1,
+                Middle => 2,
+                Right => 3,
+                Back => 8,
+                Forward => {
+                    // it needs the ExtendedMouseButtons pointer event, which gvnc can't send
+                    log::warn!("The forward button can't be sent over VNC");
+                    return;
+                }
+                button => {
+                    log::warn!("Unhandled button: {:?}", button);
+                    return;
+                }
+            };
+            self.button_event(press, button)
         }
 
         fn scroll(&self, scroll: rdw::Scroll) {
@@ -349,6 +376,26 @@ mod imp {
             self.button_event(false, n);
         }
 
+        fn set_size(&self, width: u32, height: u32, scale: f64) {
+            let (width, height) = (
+                (width as f64 / scale).round() as u32,
+                (height as f64 / scale).round() as u32,
+            );
+            if let Err(e) = self.connection.set_size(width, height) {
+                log::warn!("Failed to set size {:?}: {}", (width, height), e);
+            }
+        }
+
+        // the monitors are side by side on the single desktop surface
+        pub(crate) fn send_monitor_layout(&self, layout: &[rdw::MonitorLayout]) {
+            let width = layout.iter().map(|m| m.x.max(0) as u32 + m.width).max();
+            let height = layout.iter().map(|m| m.y.max(0) as u32 + m.height).max();
+            if let (Some(width), Some(height)) = (width, height) {
+                let scale = layout.iter().find(|m| m.primary).map_or(1.0, |m| m.scale);
+                self.set_size(width, height, scale);
+            }
+        }
+
         fn do_framebuffer_init(&self) {
             let remote_format = self.connection.pixel_format().unwrap();
             let (width, height) = (self.connection.width(), self.connection.height());
@@ -422,6 +469,11 @@ mod imp {
                 enc.retain(|&x| x != ExtKeyEvent);
             }
 
+            // until the server replies with its ExtendedDesktopSize
+            self.obj().set_resize_supported(false);
+            self.probing_resize
+                .set(enc.contains(&ExtendedDesktopResize));
+
             let enc: Vec<i32> = enc.into_iter().map(|x| x.into_glib()).collect();
             self.connection.set_encodings(&enc)?;
 
@@ -432,7 +484,7 @@ mod imp {
 }
 
 glib::wrapper! {
-    pub struct Display(ObjectSubclass<imp::Display>) @extends rdw::Display, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+    pub struct Display(ObjectSubclass<imp::Display>) @extends rdw::Display, gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
 }
 
 impl Display {
@@ -445,6 +497,14 @@ impl Display {
 
         &imp.connection
     }
+
+    /// The manager of the session monitors, resizing the single VNC desktop to fit them.
+    pub fn monitor_manager(&self) -> rdw::MonitorManager {
+        let imp = imp::Display::from_obj(self);
+        rdw::MonitorManager::for_display(self, &imp.monitors, |this, layout| {
+            imp::Display::from_obj(this).send_monitor_layout(layout);
+        })
+    }
 }
 
 impl Default for Display {
@@ -108,6 +108,36 @@ pub extern "C" fn rdw_display_update_area(
     this.update_area(x, y, w, h, stride, data);
 }
 
+/// rdw_display_update_area_with_format:
+/// @dpy: A #RdwDisplay
+/// @format: A #RdwPixelFormat
+/// @data: (array) (element-type guint8): data
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn rdw_display_update_area_with_format(
+    dpy: *mut RdwDisplay,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    stride: i32,
+    format: crate::ffi::RdwPixelFormat,
+    data: *const u8,
+) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    let format: crate::PixelFormat = unsafe { from_glib(format) };
+    let data = unsafe { std::slice::from_raw_parts(data, (h * stride) as _) };
+    this.update_area_with_format(x, y, w, h, stride, format, data);
+}
+
+/// rdw_display_add_damage:
+/// @dpy: A #RdwDisplay
+#[no_mangle]
+pub extern "C" fn rdw_display_add_damage(dpy: *mut RdwDisplay, x: i32, y: i32, w: i32, h: i32) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    this.add_damage(x, y, w, h);
+}
+
 /// rdw_display_render:
 /// @dpy: A #RdwDisplay
 #[no_mangle]
@@ -116,6 +146,104 @@ pub extern "C" fn rdw_display_render(dpy: *mut RdwDisplay) {
     this.render();
 }
 
+/// rdw_display_snapshot_texture:
+/// @dpy: A #RdwDisplay
+///
+/// Returns: (transfer full) (nullable): the remote display content, at its native resolution.
+#[no_mangle]
+pub extern "C" fn rdw_display_snapshot_texture(dpy: *mut RdwDisplay) -> *mut gdk::ffi::GdkTexture {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    this.snapshot_texture().to_glib_full()
+}
+
+/// rdw_display_send_keys:
+/// @dpy: A #RdwDisplay
+/// @keyvals: (array length=n_keyvals): the keys, pressed in order and released in reverse
+/// @n_keyvals: the number of keys
+#[no_mangle]
+pub extern "C" fn rdw_display_send_keys(
+    dpy: *mut RdwDisplay,
+    keyvals: *const u32,
+    n_keyvals: usize,
+) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    let keyvals = if n_keyvals == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(keyvals, n_keyvals) }
+    };
+    let keyvals: Vec<gdk::Key> = keyvals.iter().map(|k| unsafe { from_glib(*k) }).collect();
+    this.send_keys(&keyvals);
+}
+
+/// rdw_display_send_text:
+/// @dpy: A #RdwDisplay
+/// @text: the UTF-8 text to type
+#[no_mangle]
+pub extern "C" fn rdw_display_send_text(dpy: *mut RdwDisplay, text: *const std::os::raw::c_char) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    let text: glib::GString = unsafe { from_glib_none(text) };
+    this.send_text(&text);
+}
+
+/// rdw_display_type_text:
+/// @dpy: A #RdwDisplay
+/// @text: the UTF-8 text to type, one char every #RdwDisplay:typing-delay ms
+#[no_mangle]
+pub extern "C" fn rdw_display_type_text(dpy: *mut RdwDisplay, text: *const std::os::raw::c_char) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    let text: glib::GString = unsafe { from_glib_none(text) };
+    this.type_text(&text);
+}
+
+/// rdw_display_type_clipboard:
+/// @dpy: A #RdwDisplay
+///
+/// Type the local clipboard text.
+#[no_mangle]
+pub extern "C" fn rdw_display_type_clipboard(dpy: *mut RdwDisplay) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    this.type_clipboard();
+}
+
+/// rdw_display_cancel_typing:
+/// @dpy: A #RdwDisplay
+#[no_mangle]
+pub extern "C" fn rdw_display_cancel_typing(dpy: *mut RdwDisplay) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    this.cancel_typing();
+}
+
+/// rdw_display_set_supported_modes:
+/// @dpy: A #RdwDisplay
+/// @modes: (array length=n_modes): width and height pairs, in device pixels
+/// @n_modes: the number of modes
+#[no_mangle]
+pub extern "C" fn rdw_display_set_supported_modes(
+    dpy: *mut RdwDisplay,
+    modes: *const u32,
+    n_modes: usize,
+) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    let modes = if n_modes == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(modes, n_modes * 2) }
+    };
+    let modes: Vec<_> = modes.chunks_exact(2).map(|m| (m[0], m[1])).collect();
+    this.set_supported_modes(&modes);
+}
+
+/// rdw_display_request_guest_resize:
+/// @dpy: A #RdwDisplay
+/// @width: the width, in device pixels
+/// @height: the height, in device pixels
+#[no_mangle]
+pub extern "C" fn rdw_display_request_guest_resize(dpy: *mut RdwDisplay, width: u32, height: u32) {
+    let this: &Display = unsafe { &from_glib_borrow(dpy) };
+    this.request_guest_resize(width, height);
+}
+
 /// rdw_display_set_dmabuf_scanout:
 /// @dpy: A #RdwDisplay
 #[cfg(unix)]
@@ -129,11 +257,11 @@ pub extern "C" fn rdw_display_set_dmabuf_scanout(
     let dmabuf = RdwDmabufScanout {
         width: dmabuf.width,
         height: dmabuf.height,
-        stride: dmabuf.stride,
         fourcc: dmabuf.fourcc,
-        modifier: dmabuf.modifier,
-        fd: dmabuf.fd,
+        n_planes: dmabuf.n_planes,
+        planes: dmabuf.planes,
         y0_top: dmabuf.y0_top,
+        acquire_fd: dmabuf.acquire_fd,
     };
     this.set_dmabuf_scanout(dmabuf);
 }
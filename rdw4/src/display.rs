@@ -1,12 +1,17 @@
 #[cfg(unix)]
 use gdk_wl::prelude::*;
 use glib::{signal::SignalHandlerId, subclass::prelude::*, translate::*};
-use gtk::{gdk, glib, prelude::*, subclass::prelude::WidgetImpl};
+use gtk::{cairo, gdk, gio, glib, prelude::*, subclass::prelude::WidgetImpl};
+use std::{future::Future, pin::Pin};
 
 #[cfg(all(unix, not(feature = "bindings")))]
 use gdk_wl::wayland_client::{self, protocol::wl_registry};
 #[cfg(all(unix, not(feature = "bindings")))]
 use wayland_protocols::wp::{
+    fractional_scale::v1::client::{
+        wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1,
+        wp_fractional_scale_v1::{self, WpFractionalScaleV1},
+    },
     pointer_constraints::zv1::client::{
         zwp_locked_pointer_v1::ZwpLockedPointerV1,
         zwp_pointer_constraints_v1::{self, ZwpPointerConstraintsV1},
@@ -19,7 +24,11 @@ use wayland_protocols::wp::{
 
 #[cfg(unix)]
 use crate::RdwDmabufScanout;
-use crate::{Grab, KeyEvent, Scroll};
+use crate::{
+    model::{ZOOM_MAX, ZOOM_MIN, ZOOM_STEP},
+    Grab, KeyEvent, MouseButton, PixelFormat, Rect, ResizePolicy, Rotation, ScalingMode, Scroll,
+    StylusFlags, TouchMode,
+};
 
 #[cfg(all(unix, not(feature = "bindings")))]
 use crate::egl;
@@ -42,10 +51,17 @@ impl std::fmt::Debug for RdwDisplay {
     }
 }
 
+impl From<Rect> for gdk::Rectangle {
+    fn from(r: Rect) -> Self {
+        gdk::Rectangle::new(r.x, r.y, r.width, r.height)
+    }
+}
+
 #[cfg(not(feature = "bindings"))]
 pub mod imp {
     use super::*;
     use crate::error::Error;
+    use crate::model::{CursorImage, DisplayModel, Geometry, InputEvent};
     use crate::util;
     #[cfg(windows)]
     use crate::win32;
@@ -63,6 +79,85 @@ pub mod imp {
     #[cfg(unix)]
     use x11::xlib;
 
+    // GL internal format, format and type of a framebuffer texture
+    pub(crate) fn gl_format(format: PixelFormat) -> (GLenum, GLenum, GLenum) {
+        match format {
+            PixelFormat::Argb8888 | PixelFormat::Argb8888Premultiplied => {
+                (gl::RGBA, gl::BGRA, gl::UNSIGNED_BYTE)
+            }
+            PixelFormat::Xbgr8888 => (gl::RGB, gl::RGBA, gl::UNSIGNED_BYTE),
+            PixelFormat::Abgr8888 | PixelFormat::Abgr8888Premultiplied => {
+                (gl::RGBA, gl::RGBA, gl::UNSIGNED_BYTE)
+            }
+            PixelFormat::Rgb565 => (gl::RGB, gl::RGB, gl::UNSIGNED_SHORT_5_6_5),
+            PixelFormat::Xrgb1555 => (gl::RGB, gl::BGRA, gl::UNSIGNED_SHORT_1_5_5_5_REV),
+            PixelFormat::Xrgb2101010 => (gl::RGB10, gl::BGRA, gl::UNSIGNED_INT_2_10_10_10_REV),
+            PixelFormat::Argb2101010 => (gl::RGB10_A2, gl::BGRA, gl::UNSIGNED_INT_2_10_10_10_REV),
+            _ => (gl::RGB, gl::BGRA, gl::UNSIGNED_BYTE),
+        }
+    }
+
+    // texture-blit.frag alpha_mode: 0 opaque, 1 straight alpha, 2 premultiplied alpha
+    fn alpha_mode(format: PixelFormat) -> GLint {
+        match format {
+            PixelFormat::Argb8888Premultiplied | PixelFormat::Abgr8888Premultiplied => 2,
+            f if f.has_alpha() => 1,
+            _ => 0,
+        }
+    }
+
+    // merge overlapping areas, or reduce them to their extents when too fragmented
+    // the cursor texture, as kept by the model
+    pub(crate) fn cursor_image(cursor: &gdk::Cursor) -> Option<CursorImage> {
+        if cursor.name().as_deref() == Some("none") {
+            return Some(CursorImage {
+                width: 1,
+                height: 1,
+                hot_x: 0,
+                hot_y: 0,
+                data: vec![0; 4],
+            });
+        }
+        let texture = cursor.texture()?;
+        let (width, height) = (texture.width() as usize, texture.height() as usize);
+        let mut data = vec![0; width * height * 4];
+        texture.download(&mut data, width * 4);
+        Some(CursorImage {
+            width,
+            height,
+            hot_x: cursor.hotspot_x() as _,
+            hot_y: cursor.hotspot_y() as _,
+            data,
+        })
+    }
+
+    fn coalesce(rects: Vec<gdk::Rectangle>) -> Option<cairo::Region> {
+        if rects.is_empty() {
+            return None;
+        }
+
+        let region = cairo::Region::create();
+        for r in rects {
+            let r = cairo::RectangleInt::new(r.x(), r.y(), r.width(), r.height());
+            if let Err(e) = region.union_rectangle(&r) {
+                log::warn!("Failed to union region: {}", e);
+            }
+        }
+        if region.num_rectangles() > MAX_REGION_RECTS {
+            return Some(cairo::Region::create_rectangle(&region.extents()));
+        }
+        Some(region)
+    }
+
+    fn rectangles(region: &cairo::Region) -> Vec<gdk::Rectangle> {
+        (0..region.num_rectangles())
+            .map(|i| {
+                let r = region.rectangle(i);
+                gdk::Rectangle::new(r.x(), r.y(), r.width(), r.height())
+            })
+            .collect()
+    }
+
     unsafe impl ClassStruct for RdwDisplayClass {
         type Type = Display;
     }
@@ -71,25 +166,121 @@ pub mod imp {
         type Type = Display;
     }
 
+    // in widget logical pixels
+    const EDGE_PAN_BORDER: f64 = 16.0;
+
+    // number of pixel buffer objects used for streaming uploads
+    const PBO_RING_SIZE: usize = 3;
+
+    // above this, a region is reduced to its extents
+    const MAX_REGION_RECTS: i32 = 32;
+
+    // set on the surfaces with a fractional scale object
+    #[cfg(unix)]
+    const FRACTIONAL_SCALE_KEY: &str = "rdw-fractional-scale";
+
+    // a touch point driving the mouse
+    #[derive(Debug)]
+    pub(crate) struct TouchEmulation {
+        sequence: usize,
+        // widget position of the touch begin
+        start: (f64, f64),
+        moved: bool,
+        pressed: bool,
+        // the long-press happened
+        done: bool,
+        long_press_id: Option<SourceId>,
+    }
+
+    // time in ms for a long-press, and distance in px to start a drag
+    const LONG_PRESS_DELAY: u64 = 500;
+    const DRAG_THRESHOLD: f64 = 8.0;
+
+    // text being typed as key events, one char per tick
+    pub(crate) struct Typing {
+        chars: Vec<char>,
+        pos: usize,
+        source: Option<SourceId>,
+    }
+
+    // composed chars, typed with a dead key followed by the base char
+    const DEAD_KEYS: &[(gdk::Key, &str, &str)] = &[
+        (gdk::Key::dead_grave, "àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+        (gdk::Key::dead_acute, "áéíóúýÁÉÍÓÚÝ", "aeiouyAEIOUY"),
+        (gdk::Key::dead_circumflex, "âêîôûÂÊÎÔÛ", "aeiouAEIOU"),
+        (gdk::Key::dead_tilde, "ãñõÃÑÕ", "anoANO"),
+        (gdk::Key::dead_diaeresis, "äëïöüÿÄËÏÖÜ", "aeiouyAEIOU"),
+        (gdk::Key::dead_cedilla, "çÇ", "cC"),
+        (gdk::Key::dead_abovering, "åÅ", "aA"),
+    ];
+
+    // what a GL frame was drawn with, to tell whether it can be updated in place
+    #[derive(Clone, Copy, PartialEq)]
+    pub(crate) struct FrameState {
+        // widget size and viewport, in device pixels
+        size: (i32, i32),
+        viewport: Option<(i32, i32, i32, i32)>,
+        transform: [f32; 4],
+        flip: bool,
+        scaling_mode: ScalingMode,
+        // the bound framebuffer and its color attachment
+        target: (GLuint, GLuint),
+    }
+
     #[derive(Default)]
     pub struct Display {
         pub(crate) gl_area: OnceCell<gtk::GLArea>,
         pub(crate) layout_manager: OnceCell<gtk::BinLayout>,
 
-        // The remote display size, ex: 1024x768
-        pub(crate) display_size: Cell<Option<(usize, usize)>>,
-        pub(crate) last_resize_request: Cell<Option<(u32, u32, u32, u32)>>,
+        // the remote display state: size, framebuffer, cursor, orientation..
+        pub(crate) model: RefCell<DisplayModel>,
+        // gtk::Scrollable state, offsets the viewport when larger than the widget
+        pub(crate) hadjustment: RefCell<Option<gtk::Adjustment>>,
+        pub(crate) hadjustment_id: Cell<Option<SignalHandlerId>>,
+        pub(crate) vadjustment: RefCell<Option<gtk::Adjustment>>,
+        pub(crate) vadjustment_id: Cell<Option<SignalHandlerId>>,
+        pub(crate) hscroll_policy: Cell<Option<gtk::ScrollablePolicy>>,
+        pub(crate) vscroll_policy: Cell<Option<gtk::ScrollablePolicy>>,
+        // scroll when the pointer is close to the widget borders while grabbed
+        pub(crate) edge_pan: Cell<bool>,
+        pub(crate) edge_pan_id: Cell<Option<SourceId>>,
+        // last pointer position in widget coordinates
+        pub(crate) last_pointer: Cell<Option<(f64, f64)>>,
+        // zoom level when the zoom gesture started
+        pub(crate) zoom_gesture_level: Cell<f64>,
+        pub(crate) touch_mode: Cell<TouchMode>,
+        // the touch sequences, by slot
+        pub(crate) touch_slots: RefCell<Vec<Option<usize>>>,
+        pub(crate) touch_emulation: RefCell<Option<TouchEmulation>>,
+        pub(crate) zoom_in_shortcut: RefCell<Option<gtk::ShortcutTrigger>>,
+        pub(crate) zoom_out_shortcut: RefCell<Option<gtk::ShortcutTrigger>>,
+        pub(crate) zoom_reset_shortcut: RefCell<Option<gtk::ShortcutTrigger>>,
+        pub(crate) last_resize_request: Cell<Option<(u32, u32, u32, u32, f64)>>,
         pub(crate) resize_timeout_id: Cell<Option<SourceId>>,
+        pub(crate) resize_policy: Cell<ResizePolicy>,
+        pub(crate) resize_debounce: Cell<u32>,
+        // text typed as key events, with a progress osd
+        pub(crate) typing: RefCell<Option<Typing>>,
+        pub(crate) typing_delay: Cell<u32>,
+        pub(crate) typing_osd: OnceCell<(gtk::Box, gtk::ProgressBar)>,
+        // the compositor preferred scale, when fractional
+        pub(crate) fractional_scale: Cell<Option<f64>>,
+        // to follow the monitor the widget is on
+        pub(crate) surface_monitor_id: RefCell<Option<(gdk::Surface, SignalHandlerId)>>,
+        // to follow the fractional scale of the surface, since GTK 4.12
+        pub(crate) surface_scale_id: RefCell<Option<(gdk::Surface, SignalHandlerId)>>,
         // The currently defined cursor
         pub(crate) cursor: RefCell<Option<gdk::Cursor>>,
-        pub(crate) mouse_absolute: Cell<bool>,
-        // position of cursor when drawn by client
-        pub(crate) cursor_position: Cell<Option<(usize, usize)>>,
+        // show a dot when the remote hides its cursor
+        pub(crate) cursor_dot: Cell<bool>,
+        pub(crate) dot_cursor: OnceCell<gdk::Cursor>,
         // press-and-release detection time in ms
         pub(crate) synthesize_delay: Cell<u32>,
         pub(crate) last_key_press: Cell<Option<(gdk::Key, u32)>>,
         pub(crate) last_key_press_timeout: Cell<Option<SourceId>>,
         pub(crate) keys_pressed: RefCell<HashSet<(gdk::Key, u32)>>,
+        // keycodes of the shortcut presses not forwarded, to drop their release
+        pub(crate) keys_swallowed: RefCell<HashSet<u32>>,
 
         // the shortcut to ungrab key/mouse (to be configurable and extended with ctrl-alt)
         pub(crate) grab_shortcut: OnceCell<gtk::ShortcutTrigger>,
@@ -105,6 +296,28 @@ pub mod imp {
         pub(crate) egl_surf: OnceCell<egl::Surface>,
 
         pub(crate) texture_id: Cell<GLuint>,
+        // the chroma plane of YUV scanouts
+        pub(crate) texture_uv_id: Cell<GLuint>,
+        // framebuffer areas to upload to the texture on the next frame
+        pub(crate) pending_uploads: RefCell<Vec<gdk::Rectangle>>,
+        // the texture storage must be reallocated from the framebuffer
+        pub(crate) texture_stale: Cell<bool>,
+        // pixel buffer objects, empty if unsupported
+        pub(crate) pbo_ring: RefCell<Vec<GLuint>>,
+        // the last GL frame, kept in the render target
+        pub(crate) last_frame: Cell<Option<FrameState>>,
+        pub(crate) pbo_index: Cell<usize>,
+        pub(crate) upload_tick_id: RefCell<Option<gtk::TickCallbackId>>,
+        // frame time of the last upload, in µs
+        pub(crate) last_upload_time: Cell<Option<i64>>,
+        pub(crate) max_fps: Cell<u32>,
+        // render with gsk from a CPU copy, when GL is unavailable or forced
+        pub(crate) force_software: Cell<bool>,
+        pub(crate) gl_failed: Cell<bool>,
+        pub(crate) software: Cell<bool>,
+        // premultiplied BGRA copy of the framebuffer, and its texture
+        pub(crate) sw_pixels: RefCell<Vec<u8>>,
+        pub(crate) sw_texture: RefCell<Option<gdk::Texture>>,
         pub(crate) texture_blit_vao: Cell<GLuint>,
         pub(crate) texture_blit_prog: Cell<GLuint>,
         pub(crate) texture_blit_flip_prog: Cell<GLuint>,
@@ -123,6 +336,10 @@ pub mod imp {
         pub(crate) wl_pointer_constraints: OnceCell<ZwpPointerConstraintsV1>,
         #[cfg(unix)]
         pub(crate) wl_lock_pointer: RefCell<Option<ZwpLockedPointerV1>>,
+        #[cfg(unix)]
+        pub(crate) wl_fractional_manager: OnceCell<WpFractionalScaleManagerV1>,
+        #[cfg(unix)]
+        pub(crate) wl_fractional_scale: RefCell<Option<WpFractionalScaleV1>>,
 
         #[cfg(windows)]
         pub(crate) win_mouse: Cell<[isize; 3]>,
@@ -143,8 +360,9 @@ pub mod imp {
         type ParentType = gtk::Widget;
         type Class = RdwDisplayClass;
         type Instance = RdwDisplay;
+        type Interfaces = (gtk::Scrollable,);
 
-        fn class_init(_klass: &mut Self::Class) {
+        fn class_init(klass: &mut Self::Class) {
             // Load GL pointers from epoxy (GL context management library used by GTK).
             {
                 #[cfg(target_os = "macos")]
@@ -168,6 +386,22 @@ pub mod imp {
                 });
                 gl::load_with(epoxy::get_proc_addr);
             }
+
+            klass.install_action("display.zoom-in", None, |obj, _, _| {
+                obj.zoom_in();
+            });
+            klass.install_action("display.zoom-out", None, |obj, _, _| {
+                obj.zoom_out();
+            });
+            klass.install_action("display.zoom-reset", None, |obj, _, _| {
+                obj.zoom_reset();
+            });
+            klass.install_action("display.type-clipboard", None, |obj, _, _| {
+                obj.type_clipboard();
+            });
+            klass.install_action("display.cancel-typing", None, |obj, _, _| {
+                obj.cancel_typing();
+            });
         }
     }
 
@@ -176,6 +410,11 @@ pub mod imp {
             self.parent_constructed();
             self.layout_manager.set(gtk::BinLayout::new()).unwrap();
 
+            self.obj()
+                .connect_scale_factor_notify(clone!(@weak self as this => move |_| {
+                    this.queue_resize_request();
+                }));
+
             let gl_area = gtk::GLArea::new();
             gl_area.set_has_depth_buffer(false);
             gl_area.set_has_stencil_buffer(false);
@@ -187,19 +426,62 @@ pub mod imp {
                     glib::signal::Inhibit(true)
                 }),
             );
-            gl_area.connect_realize(clone!(@weak self as this => move |_| {
-                if let Err(e) = unsafe { this.realize_gl() } {
+            gl_area.connect_realize(clone!(@weak self as this => move |area| {
+                if let Some(e) = area.error() {
+                    log::warn!("Failed to create gl context: {}", e);
+                    this.gl_failed.set(true);
+                } else if let Err(e) = unsafe { this.realize_gl() } {
                     log::warn!("Failed to realize gl: {}", e);
                     let e = glib::Error::new(Error::GL, &e);
-                    this.gl_area().set_error(Some(&e));
+                    area.set_error(Some(&e));
+                    this.gl_failed.set(true);
                 }
+                this.update_software();
             }));
+            gl_area.set_visible(!self.software.get());
 
             self.gl_area.set(gl_area).unwrap();
 
+            if self.hadjustment.borrow().is_none() {
+                self.set_adjustment(gtk::Orientation::Horizontal, None);
+            }
+            if self.vadjustment.borrow().is_none() {
+                self.set_adjustment(gtk::Orientation::Vertical, None);
+            }
+
             self.grab_shortcut.get_or_init(|| {
                 gtk::ShortcutTrigger::parse_string("<Ctrl>Alt_L|<Alt>Control_L").unwrap()
             });
+            let init_shortcut = |shortcut: &RefCell<Option<gtk::ShortcutTrigger>>, s| {
+                if shortcut.borrow().is_none() {
+                    shortcut.replace(gtk::ShortcutTrigger::parse_string(s));
+                }
+            };
+            init_shortcut(&self.zoom_in_shortcut, "<Ctrl><Alt>plus|<Ctrl><Alt>KP_Add");
+            init_shortcut(
+                &self.zoom_out_shortcut,
+                "<Ctrl><Alt>minus|<Ctrl><Alt>KP_Subtract",
+            );
+            init_shortcut(&self.zoom_reset_shortcut, "<Ctrl><Alt>0|<Ctrl><Alt>KP_0");
+
+            let progress = gtk::ProgressBar::new();
+            progress.set_size_request(200, -1);
+            progress.set_valign(gtk::Align::Center);
+            let cancel = gtk::Button::from_icon_name("process-stop-symbolic");
+            cancel.set_tooltip_text(Some("Cancel typing"));
+            cancel.set_focus_on_click(false);
+            cancel.set_action_name(Some("display.cancel-typing"));
+            let osd = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+            osd.add_css_class("osd");
+            osd.add_css_class("toolbar");
+            osd.set_halign(gtk::Align::Center);
+            osd.set_valign(gtk::Align::End);
+            osd.set_margin_bottom(12);
+            osd.append(&progress);
+            osd.append(&cancel);
+            osd.set_visible(false);
+            osd.set_parent(&*self.obj());
+            self.typing_osd.set((osd, progress)).unwrap();
         }
 
         fn dispose(&self) {
@@ -207,6 +489,15 @@ pub mod imp {
             if let Some(source) = self.wl_source.take() {
                 source.remove();
             }
+            if let Some(source) = self.edge_pan_id.take() {
+                source.remove();
+            }
+            if let Some(source) = self.resize_timeout_id.take() {
+                source.remove();
+            }
+            if let Some(source) = self.typing.take().and_then(|t| t.source) {
+                source.remove();
+            }
             while let Some(child) = self.obj().first_child() {
                 child.unparent();
             }
@@ -248,6 +539,155 @@ pub mod imp {
                         false,
                         Flags::READWRITE | Flags::CONSTRUCT,
                     ),
+                    glib::ParamSpecEnum::new(
+                        "scaling-mode",
+                        "Scaling mode",
+                        "How the remote display is scaled in the widget",
+                        ScalingMode::static_type(),
+                        ScalingMode::Fit.into_glib(),
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecEnum::new(
+                        "rotation",
+                        "Rotation",
+                        "Clockwise rotation of the remote display",
+                        Rotation::static_type(),
+                        Rotation::Normal.into_glib(),
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "flip-horizontal",
+                        "Flip horizontal",
+                        "Whether the remote display is mirrored horizontally",
+                        false,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "flip-vertical",
+                        "Flip vertical",
+                        "Whether the remote display is mirrored vertically",
+                        false,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "edge-pan",
+                        "Edge pan",
+                        "Whether to scroll when the pointer is near a border while grabbed",
+                        false,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecUInt::new(
+                        "max-fps",
+                        "Max FPS",
+                        "Maximum rate of framebuffer updates (0 to follow the frame clock)",
+                        0,
+                        u32::MAX,
+                        0,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "force-software",
+                        "Force software",
+                        "Render without GL, even if it is available",
+                        false,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "software-rendering",
+                        "Software rendering",
+                        "Whether the display is rendered without GL",
+                        false,
+                        Flags::READABLE,
+                    ),
+                    glib::ParamSpecEnum::new(
+                        "touch-mode",
+                        "Touch mode",
+                        "How touch events are sent to the remote",
+                        TouchMode::static_type(),
+                        TouchMode::EmulateMouse.into_glib(),
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "cursor-dot",
+                        "Cursor dot",
+                        "Whether to show a dot when the remote hides its cursor",
+                        false,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecDouble::new(
+                        "zoom-level",
+                        "Zoom level",
+                        "Zoom factor applied on top of the scaling mode",
+                        ZOOM_MIN,
+                        ZOOM_MAX,
+                        1.0,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecEnum::new(
+                        "resize-policy",
+                        "Resize policy",
+                        "When to ask the remote to resize to the widget",
+                        ResizePolicy::static_type(),
+                        ResizePolicy::OnIdle.into_glib(),
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecUInt::new(
+                        "resize-debounce",
+                        "Resize debounce",
+                        "Time in ms the widget size must be stable before a resize-request",
+                        0,
+                        u32::MAX,
+                        500,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "resize-supported",
+                        "Resize supported",
+                        "Whether the remote can be resized",
+                        true,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecUInt::new(
+                        "typing-delay",
+                        "Typing delay",
+                        "Time in ms between the chars typed with type_text",
+                        1,
+                        u32::MAX,
+                        20,
+                        Flags::READWRITE | Flags::CONSTRUCT,
+                    ),
+                    glib::ParamSpecBoolean::new(
+                        "typing",
+                        "Typing",
+                        "Whether some text is being typed",
+                        false,
+                        Flags::READABLE,
+                    ),
+                    glib::ParamSpecObject::new(
+                        "zoom-in-shortcut",
+                        "Zoom in shortcut",
+                        "Zoom in shortcut",
+                        gtk::ShortcutTrigger::static_type(),
+                        Flags::READWRITE,
+                    ),
+                    glib::ParamSpecObject::new(
+                        "zoom-out-shortcut",
+                        "Zoom out shortcut",
+                        "Zoom out shortcut",
+                        gtk::ShortcutTrigger::static_type(),
+                        Flags::READWRITE,
+                    ),
+                    glib::ParamSpecObject::new(
+                        "zoom-reset-shortcut",
+                        "Zoom reset shortcut",
+                        "Zoom reset shortcut",
+                        gtk::ShortcutTrigger::static_type(),
+                        Flags::READWRITE,
+                    ),
+                    glib::ParamSpecOverride::for_interface::<gtk::Scrollable>("hadjustment"),
+                    glib::ParamSpecOverride::for_interface::<gtk::Scrollable>("vadjustment"),
+                    glib::ParamSpecOverride::for_interface::<gtk::Scrollable>("hscroll-policy"),
+                    glib::ParamSpecOverride::for_interface::<gtk::Scrollable>("vscroll-policy"),
                 ]
             });
             PROPERTIES.as_ref()
@@ -267,10 +707,113 @@ pub mod imp {
                     let absolute = value.get().unwrap();
                     if absolute {
                         self.ungrab_mouse();
-                        self.gl_area().set_cursor(self.cursor.borrow().as_ref());
+                        self.set_widget_cursor(self.shown_cursor().as_ref());
                     }
 
-                    self.mouse_absolute.set(absolute);
+                    self.model.borrow_mut().set_mouse_absolute(absolute);
+                }
+                "scaling-mode" => {
+                    let mode = value.get().unwrap();
+                    if self.model.borrow_mut().set_scaling_mode(mode) {
+                        self.configure_adjustments();
+                        self.obj().queue_resize();
+                        self.queue_render();
+                    }
+                }
+                "rotation" => {
+                    let rotation = value.get().unwrap();
+                    if self.model.borrow_mut().set_rotation(rotation) {
+                        self.orientation_changed();
+                    }
+                }
+                "flip-horizontal" => {
+                    let flip = value.get().unwrap();
+                    if self.model.borrow_mut().set_flip_horizontal(flip) {
+                        self.orientation_changed();
+                    }
+                }
+                "flip-vertical" => {
+                    let flip = value.get().unwrap();
+                    if self.model.borrow_mut().set_flip_vertical(flip) {
+                        self.orientation_changed();
+                    }
+                }
+                "edge-pan" => {
+                    let edge_pan = value.get().unwrap();
+                    self.edge_pan.set(edge_pan);
+                    self.update_edge_pan();
+                }
+                "max-fps" => {
+                    self.max_fps.set(value.get().unwrap());
+                }
+                "force-software" => {
+                    self.force_software.set(value.get().unwrap());
+                    self.update_software();
+                }
+                "touch-mode" => {
+                    let mode = value.get().unwrap();
+                    if self.touch_mode.replace(mode) != mode {
+                        self.touch_cancel_all();
+                    }
+                }
+                "cursor-dot" => {
+                    self.cursor_dot.set(value.get().unwrap());
+                    if self.obj().mouse_absolute() {
+                        self.set_widget_cursor(self.shown_cursor().as_ref());
+                    }
+                    self.obj().queue_draw(); // update cursor
+                }
+                "zoom-level" => {
+                    let level = value.get().unwrap();
+                    self.set_zoom_level(level, None);
+                }
+                "resize-policy" => {
+                    let policy = value.get().unwrap();
+                    if self.resize_policy.replace(policy) != policy {
+                        self.queue_resize_request();
+                    }
+                }
+                "resize-debounce" => {
+                    self.resize_debounce.set(value.get().unwrap());
+                }
+                "typing-delay" => {
+                    self.typing_delay.set(value.get().unwrap());
+                }
+                "resize-supported" => {
+                    let supported = value.get().unwrap();
+                    if self.model.borrow_mut().set_resize_supported(supported) && supported {
+                        self.last_resize_request.set(None);
+                        self.queue_resize_request();
+                    }
+                }
+                "zoom-in-shortcut" => {
+                    self.zoom_in_shortcut.replace(value.get().unwrap());
+                }
+                "zoom-out-shortcut" => {
+                    self.zoom_out_shortcut.replace(value.get().unwrap());
+                }
+                "zoom-reset-shortcut" => {
+                    self.zoom_reset_shortcut.replace(value.get().unwrap());
+                }
+                "hadjustment" => {
+                    let adj = value.get().unwrap();
+                    self.set_adjustment(gtk::Orientation::Horizontal, adj);
+                }
+                "vadjustment" => {
+                    let adj = value.get().unwrap();
+                    self.set_adjustment(gtk::Orientation::Vertical, adj);
+                }
+                "hscroll-policy" => {
+                    let policy = value.get().unwrap();
+                    if self.hscroll_policy.replace(Some(policy)) != Some(policy) {
+                        self.obj().queue_resize();
+                    }
+                }
+                "vscroll-policy" => {
+                    let policy = value.get().unwrap();
+                    if self.vscroll_policy.replace(Some(policy)) != Some(policy) {
+                        self.obj().queue_resize();
+                    }
                 }
                 _ => unimplemented!(),
             }
@@ -281,7 +824,38 @@ pub mod imp {
                 "grab-shortcut" => self.grab_shortcut.get().to_value(),
                 "grabbed" => self.grabbed.get().to_value(),
                 "synthesize-delay" => self.synthesize_delay.get().to_value(),
-                "mouse-absolute" => self.mouse_absolute.get().to_value(),
+                "mouse-absolute" => self.model.borrow().mouse_absolute().to_value(),
+                "scaling-mode" => self.scaling_mode().to_value(),
+                "rotation" => self.rotation().to_value(),
+                "flip-horizontal" => self.flip_horizontal().to_value(),
+                "flip-vertical" => self.flip_vertical().to_value(),
+                "edge-pan" => self.edge_pan.get().to_value(),
+                "max-fps" => self.max_fps.get().to_value(),
+                "force-software" => self.force_software.get().to_value(),
+                "software-rendering" => self.software.get().to_value(),
+                "touch-mode" => self.touch_mode.get().to_value(),
+                "cursor-dot" => self.cursor_dot.get().to_value(),
+                "zoom-level" => self.zoom_level().to_value(),
+                "resize-policy" => self.resize_policy.get().to_value(),
+                "resize-debounce" => self.resize_debounce.get().to_value(),
+                "typing-delay" => self.typing_delay.get().to_value(),
+                "typing" => self.typing.borrow().is_some().to_value(),
+                "resize-supported" => self.model.borrow().resize_supported().to_value(),
+                "zoom-in-shortcut" => self.zoom_in_shortcut.borrow().to_value(),
+                "zoom-out-shortcut" => self.zoom_out_shortcut.borrow().to_value(),
+                "zoom-reset-shortcut" => self.zoom_reset_shortcut.borrow().to_value(),
+                "hadjustment" => self.hadjustment.borrow().to_value(),
+                "vadjustment" => self.vadjustment.borrow().to_value(),
+                "hscroll-policy" => self
+                    .hscroll_policy
+                    .get()
+                    .unwrap_or(gtk::ScrollablePolicy::Minimum)
+                    .to_value(),
+                "vscroll-policy" => self
+                    .vscroll_policy
+                    .get()
+                    .unwrap_or(gtk::ScrollablePolicy::Minimum)
+                    .to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -303,22 +877,58 @@ pub mod imp {
                         .param_types([f64::static_type(), f64::static_type()])
                         .build(),
                     Signal::builder("mouse-press")
-                        .param_types([u32::static_type()])
+                        .param_types([MouseButton::static_type()])
                         .build(),
                     Signal::builder("mouse-release")
-                        .param_types([u32::static_type()])
+                        .param_types([MouseButton::static_type()])
                         .build(),
                     Signal::builder("scroll-discrete")
                         .param_types([Scroll::static_type()])
                         .build(),
+                    Signal::builder("scroll-smooth")
+                        .param_types([
+                            f64::static_type(),
+                            f64::static_type(),
+                            i32::static_type(),
+                            i32::static_type(),
+                        ])
+                        .build(),
+                    Signal::builder("stylus")
+                        .param_types([
+                            f64::static_type(),
+                            f64::static_type(),
+                            f64::static_type(),
+                            f64::static_type(),
+                            f64::static_type(),
+                            f64::static_type(),
+                            StylusFlags::static_type(),
+                        ])
+                        .build(),
+                    Signal::builder("touch-begin")
+                        .param_types([u32::static_type(), f64::static_type(), f64::static_type()])
+                        .build(),
+                    Signal::builder("touch-update")
+                        .param_types([u32::static_type(), f64::static_type(), f64::static_type()])
+                        .build(),
+                    Signal::builder("touch-end")
+                        .param_types([u32::static_type()])
+                        .build(),
+                    Signal::builder("touch-cancel")
+                        .param_types([u32::static_type()])
+                        .build(),
                     Signal::builder("resize-request")
                         .param_types([
                             u32::static_type(),
                             u32::static_type(),
                             u32::static_type(),
                             u32::static_type(),
+                            f64::static_type(),
                         ])
                         .build(),
+                    Signal::builder("frame-damage")
+                        .param_types([cairo::Region::static_type()])
+                        .build(),
+                    Signal::builder("dmabuf-released").build(),
                 ]
             });
             SIGNALS.as_ref()
@@ -326,6 +936,29 @@ pub mod imp {
     }
 
     impl WidgetImpl for Display {
+        fn unrealize(&self) {
+            if let Some((surface, id)) = self.surface_monitor_id.take() {
+                surface.disconnect(id);
+            }
+            if let Some((surface, id)) = self.surface_scale_id.take() {
+                surface.disconnect(id);
+                self.fractional_scale.set(None);
+            }
+            self.last_frame.set(None);
+            #[cfg(unix)]
+            self.destroy_fractional_scale();
+            // before the GL area and its context go away
+            let pbos = self.pbo_ring.take();
+            if !pbos.is_empty() {
+                let _ctx = self.make_current();
+                unsafe {
+                    gl::DeleteBuffers(pbos.len() as _, pbos.as_ptr());
+                }
+                self.pbo_index.set(0);
+            }
+            self.parent_unrealize();
+        }
+
         fn realize(&self) {
             self.parent_realize();
 
@@ -342,9 +975,12 @@ pub mod imp {
             if self.realize_egl() {
                 if let Err(e) = unsafe { self.realize_gl() } {
                     log::warn!("Failed to realize GL: {}", e);
+                    self.gl_failed.set(true);
+                    self.update_software();
                 }
             } else {
-                self.gl_area().set_parent(&*self.obj());
+                // below the typing osd
+                self.gl_area().insert_after(&*self.obj(), gtk::Widget::NONE);
             }
 
             #[cfg(unix)]
@@ -352,6 +988,27 @@ pub mod imp {
                 self.realize_wl(&dpy);
             }
 
+            if let Some(surface) = self.surface() {
+                let id = surface.connect_enter_monitor(clone!(@weak self as this => move |_, _| {
+                    this.queue_resize_request();
+                }));
+                self.surface_monitor_id.replace(Some((surface.clone(), id)));
+
+                // GTK binds the fractional scale protocol itself since 4.12
+                if gtk::check_version(4, 12, 0).is_none()
+                    && surface.find_property("scale").is_some()
+                {
+                    let id = surface.connect_notify_local(
+                        Some("scale"),
+                        clone!(@weak self as this => move |surface, _| {
+                            this.surface_scale_changed(surface);
+                        }),
+                    );
+                    self.surface_scale_changed(&surface);
+                    self.surface_scale_id.replace(Some((surface, id)));
+                }
+            }
+
             #[cfg(windows)]
             if let Ok(dpy) = self.obj().display().downcast::<gdk_win32::Win32Display>() {
                 self.realize_win32(&dpy);
@@ -374,18 +1031,25 @@ pub mod imp {
 
             let ec = gtk::EventControllerMotion::new();
             self.obj().add_controller(&ec);
-            ec.connect_motion(clone!(@weak self as this => move |_, x, y| {
-                if let Some((x, y)) = this.transform_pos(x, y) {
-                    this.obj().emit_by_name::<()>("motion", &[&x, &y]);
+            ec.connect_motion(clone!(@weak self as this => move |ec, x, y| {
+                if this.stylus_handled(ec) {
+                    return;
+                }
+                this.last_pointer.set(Some((x, y)));
+                this.update_edge_pan();
+                if let Some(event) = this.pointer_motion(x, y) {
+                    this.emit_input(event);
                 }
             }));
             ec.connect_enter(clone!(@weak self as this => move |_, x, y| {
-                if let Some((x, y)) = this.transform_pos(x, y) {
-                    this.obj().emit_by_name::<()>("motion", &[&x, &y]);
+                this.last_pointer.set(Some((x, y)));
+                if let Some(event) = this.pointer_motion(x, y) {
+                    this.emit_input(event);
                 }
             }));
             ec.connect_leave(clone!(@weak self as this => move |_| {
                 log::debug!("leave -> ungrab");
+                this.last_pointer.set(None);
                 this.ungrab_keyboard();
                 this.ungrab_mouse();
             }));
@@ -395,6 +1059,10 @@ pub mod imp {
             self.obj().add_controller(&ec);
             ec.connect_pressed(
                 clone!(@weak self as this => @default-panic, move |gesture, _n_press, x, y| {
+                    if gesture.current_sequence().is_some() || this.stylus_handled(gesture) {
+                        // handled by the touch or stylus controller
+                        return;
+                    }
                     let grabbed = this.try_grab();
 
                     if grabbed.contains(Grab::MOUSE) {
@@ -402,27 +1070,76 @@ pub mod imp {
                         return;
                     }
 
-                    let button = gesture.current_button();
-                    if let Some((x, y)) = this.transform_pos(x, y) {
-                        this.obj().emit_by_name::<()>("motion", &[&x, &y]);
+                    let Some(button) = MouseButton::from_gdk(gesture.current_button()) else {
+                        log::debug!("Unhandled button: {}", gesture.current_button());
+                        return;
+                    };
+                    if let Some(event) = this.pointer_motion(x, y) {
+                        this.emit_input(event);
                     }
-                    this.obj().emit_by_name::<()>("mouse-press", &[&button]);
+                    this.emit_input(InputEvent::MousePress(button));
                 }),
             );
             ec.connect_released(
                 clone!(@weak self as this => move |gesture, _n_press, x, y| {
-                    let button = gesture.current_button();
-                    if let Some((x, y)) = this.transform_pos(x, y) {
-                        this.obj().emit_by_name::<()>("motion", &[&x, &y]);
+                    if gesture.current_sequence().is_some() || this.stylus_handled(gesture) {
+                        return;
+                    }
+                    let Some(button) = MouseButton::from_gdk(gesture.current_button()) else {
+                        return;
+                    };
+                    if let Some(event) = this.pointer_motion(x, y) {
+                        this.emit_input(event);
                     }
-                    this.obj().emit_by_name::<()>("mouse-release", &[&button]);
+                    this.emit_input(InputEvent::MouseRelease(button));
+                }),
+            );
+            ec.connect_cancel(clone!(@weak self as this => move |gesture, sequence| {
+                if sequence.is_some() {
+                    return;
+                }
+                if let Some(button) = MouseButton::from_gdk(gesture.current_button()) {
+                    this.emit_input(InputEvent::MouseRelease(button));
+                }
+            }));
+
+            let ec = gtk::GestureStylus::new();
+            self.obj().add_controller(&ec);
+            ec.connect_proximity(clone!(@weak self as this => move |gesture, x, y| {
+                this.stylus_event(gesture, x, y, false);
+            }));
+            ec.connect_down(clone!(@weak self as this => move |gesture, x, y| {
+                this.try_grab();
+                this.stylus_event(gesture, x, y, true);
+            }));
+            ec.connect_motion(clone!(@weak self as this => move |gesture, x, y| {
+                this.stylus_event(gesture, x, y, true);
+            }));
+            ec.connect_up(clone!(@weak self as this => move |gesture, x, y| {
+                this.stylus_event(gesture, x, y, false);
+            }));
+
+            let ec = gtk::EventControllerLegacy::new();
+            ec.set_propagation_phase(gtk::PropagationPhase::Capture);
+            self.obj().add_controller(&ec);
+            ec.connect_event(
+                clone!(@weak self as this => @default-panic, move |_, event| {
+                    glib::signal::Inhibit(this.touch_event(event))
                 }),
             );
-            ec.connect_cancel(clone!(@weak self as this => move |gesture, _| {
-                let button = gesture.current_button();
-                this.obj().emit_by_name::<()>("mouse-release", &[&button]);
+
+            let ec = gtk::GestureZoom::new();
+            self.obj().add_controller(&ec);
+            ec.connect_begin(clone!(@weak self as this => move |_, _| {
+                this.zoom_gesture_level.set(this.zoom_level());
+            }));
+            ec.connect_scale_changed(clone!(@weak self as this => move |gesture, scale| {
+                let level = this.zoom_gesture_level.get() * scale;
+                this.set_zoom_level(level, gesture.bounding_box_center());
+                this.obj().notify("zoom-level");
             }));
 
+            // GTK accumulates the smooth deltas into discrete steps
             let ec = gtk::EventControllerScroll::new(
                 gtk::EventControllerScrollFlags::BOTH_AXES
                     | gtk::EventControllerScrollFlags::DISCRETE,
@@ -430,37 +1147,63 @@ pub mod imp {
             self.obj().add_controller(&ec);
             ec.connect_scroll(
                 clone!(@weak self as this => @default-panic, move |_, dx, dy| {
-                    if dy >= 1.0 {
-                        this.obj().emit_by_name::<()>("scroll-discrete", &[&Scroll::Down]);
-                    } else if dy <= -1.0 {
-                        this.obj().emit_by_name::<()>("scroll-discrete", &[&Scroll::Up]);
-                    }
-                    if dx >= 1.0 {
-                        this.obj().emit_by_name::<()>("scroll-discrete", &[&Scroll::Right]);
-                    } else if dx <= -1.0 {
-                        this.obj().emit_by_name::<()>("scroll-discrete", &[&Scroll::Left]);
+                    let events = this.model.borrow().scroll_discrete(dx, dy);
+                    for event in events {
+                        this.emit_input(event);
                     }
                     glib::signal::Inhibit(false)
                 }),
             );
+
+            let ec = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::BOTH_AXES);
+            self.obj().add_controller(&ec);
+            ec.connect_scroll(
+                clone!(@weak self as this => @default-panic, move |ec, dx, dy| {
+                    // touchpads scroll in surface units, as GDK 4.8 reports with the scroll unit
+                    let wheel = ec.current_event_device().map_or(true, |d| {
+                        !matches!(
+                            d.source(),
+                            gdk::InputSource::Touchpad | gdk::InputSource::Trackpoint
+                        )
+                    });
+                    let event = this.model.borrow().scroll_smooth(dx, dy, wheel);
+                    this.emit_input(event);
+                    glib::signal::Inhibit(false)
+                }),
+            );
         }
 
         fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
-            let (minimum, mut natural, minimum_baseline, natural_baseline) = (128, 128, -1, -1);
+            let (mut minimum, mut natural, minimum_baseline, natural_baseline) = (128, 128, -1, -1);
 
             // TODO: doesn't work as expected yet
-            if let Some((w, h)) = self.display_size.get() {
-                match orientation {
-                    gtk::Orientation::Horizontal => {
-                        natural = w as _;
-                    }
-                    gtk::Orientation::Vertical => {
-                        natural = h as _;
-                    }
+            if let Some((w, h)) = self.oriented_size() {
+                let size = match orientation {
+                    gtk::Orientation::Horizontal => w,
+                    gtk::Orientation::Vertical => h,
                     _ => panic!(),
+                };
+                natural = size as _;
+                if self.scaling_mode() == ScalingMode::Original {
+                    // 1:1 pixels, the widget should have the remote display size
+                    let sf = self.obj().scale_factor() as f64;
+                    natural = (size as f64 * self.zoom_level() / sf) as _;
+                    // ..unless it is scrolled, then we want the scrolled window viewport size
+                    if self.is_scrolled() {
+                        minimum = minimum.min(natural);
+                    } else {
+                        minimum = natural;
+                    }
                 }
             }
 
+            // the bin layout allocates the typing osd the whole widget
+            if let Some((osd, _)) = self.typing_osd.get().filter(|(osd, _)| osd.is_visible()) {
+                let (osd_minimum, ..) = osd.measure(orientation, -1);
+                minimum = minimum.max(osd_minimum);
+                natural = natural.max(minimum);
+            }
+
             (minimum, natural, minimum_baseline, natural_baseline)
         }
 
@@ -470,73 +1213,99 @@ pub mod imp {
                 .get()
                 .unwrap()
                 .allocate(&*self.obj(), width, height, baseline);
+            self.configure_adjustments();
 
-            if let Some(timeout_id) = self.resize_timeout_id.take() {
-                timeout_id.remove();
-            }
-            self.resize_timeout_id.set(Some(glib::timeout_add_local(
-                Duration::from_millis(500),
-                clone!(@weak self as this => @default-return glib::Continue(false), move || {
-                    let sf = this.obj().scale_factor() as u32;
-                    let width = width as u32 * sf;
-                    let height = height as u32 * sf;
-                    let (w_mm, h_mm) = this.surface()
-                                   .as_ref()
-                                   .map(|s| gdk::traits::DisplayExt::monitor_at_surface(&this.obj().display(), s))
-                                   .map(|m| {
-                                       let (geom, wmm, hmm) = (m.geometry(), m.width_mm() as u32, m.height_mm() as u32);
-                                       (wmm * width / (geom.width() as u32), hmm * height / geom.height() as u32)
-                                   }).unwrap_or((0u32, 0u32));
-                    if Some((width, height, w_mm, h_mm)) != this.last_resize_request.get() {
-                        this.last_resize_request.set(Some((width, height, w_mm, h_mm)));
-                        this.obj().emit_by_name::<()>("resize-request", &[&width, &height, &w_mm, &h_mm]);
-                    }
-                    this.resize_timeout_id.set(None);
-                    glib::Continue(false)
-                }),
-            )));
+            self.queue_resize_request();
         }
 
         fn snapshot(&self, snapshot: &gtk::Snapshot) {
             snapshot.save();
-            self.parent_snapshot(snapshot);
+            if self.software.get() {
+                // the typing osd is drawn above the framebuffer
+                let osd = self
+                    .typing_osd
+                    .get()
+                    .map(|(osd, _)| osd.upcast_ref::<gtk::Widget>());
+                let mut child = self.obj().first_child();
+                while let Some(widget) = child {
+                    if Some(&widget) != osd {
+                        self.obj().snapshot_child(&widget, snapshot);
+                    }
+                    child = widget.next_sibling();
+                }
+            } else {
+                self.parent_snapshot(snapshot);
+            }
             snapshot.restore();
 
+            if self.software.get() {
+                self.snapshot_software(snapshot);
+                if let Some((osd, _)) = self.typing_osd.get() {
+                    self.obj().snapshot_child(osd, snapshot);
+                }
+            }
+
             if self.obj().mouse_absolute() {
                 return;
             }
             if !self.grabbed.get().contains(Grab::MOUSE) {
                 return;
             }
-            if let Some(pos) = self.cursor_position.get() {
-                if let Some(cursor) = &*self.cursor.borrow() {
-                    if let Some(texture) = cursor.texture() {
-                        // don't take hotspot as an offset (it's not for hw cursor)
-                        if let Some((x, y)) = self.transform_pos_inv(pos.0 as _, pos.1 as _) {
-                            let sf = self.obj().scale_factor();
-
-                            snapshot.append_texture(
-                                &texture,
-                                &graphene::Rect::new(
-                                    x as f32,
-                                    y as f32,
-                                    (texture.width() / sf) as f32,
-                                    (texture.height() / sf) as f32,
-                                ),
-                            );
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    pub(crate) struct ContextGuard<'a>(&'a Display);
-
-    impl Drop for ContextGuard<'_> {
-        fn drop(&mut self) {
-            self.0.clear_current();
-        }
+            let Some(pos) = self.cursor_position() else {
+                return;
+            };
+            let Some(cursor) = self.shown_cursor() else {
+                return;
+            };
+            let (Some(texture), Some((x, y)), Some((sx, sy))) = (
+                cursor.texture(),
+                self.transform_pos_inv(pos.0 as _, pos.1 as _),
+                self.display_scale(),
+            ) else {
+                return;
+            };
+            // the texture is in remote pixels, scale it like the display
+            let sf = self.obj().scale_factor() as f64;
+            let (sx, sy) = if self.is_rotated() {
+                (sy, sx)
+            } else {
+                (sx, sy)
+            };
+
+            // follow the display orientation
+            snapshot.save();
+            snapshot.translate(&graphene::Point::new(x as f32, y as f32));
+            let fx = if self.flip_horizontal() { -1.0 } else { 1.0 };
+            let fy = if self.flip_vertical() { -1.0 } else { 1.0 };
+            snapshot.scale(fx, fy);
+            snapshot.rotate(match self.rotation() {
+                Rotation::Rotate90 => 90.0,
+                Rotation::Rotate180 => 180.0,
+                Rotation::Rotate270 => 270.0,
+                _ => 0.0,
+            });
+            snapshot.scale((sx / sf) as f32, (sy / sf) as f32);
+            snapshot.append_texture(
+                &texture,
+                &graphene::Rect::new(
+                    -cursor.hotspot_x() as f32,
+                    -cursor.hotspot_y() as f32,
+                    texture.width() as f32,
+                    texture.height() as f32,
+                ),
+            );
+            snapshot.restore();
+        }
+    }
+
+    impl ScrollableImpl for Display {}
+
+    pub(crate) struct ContextGuard<'a>(&'a Display);
+
+    impl Drop for ContextGuard<'_> {
+        fn drop(&mut self) {
+            self.0.clear_current();
+        }
     }
 
     impl Display {
@@ -623,6 +1392,13 @@ pub mod imp {
             self.wl_pointer_constraints
                 .set(pointer_constraints)
                 .unwrap();
+            // GTK binds it itself since 4.12
+            if gtk::check_version(4, 12, 0).is_some() {
+                if let Ok(manager) = globals.bind(&queue.handle(), 1..=1, ()) {
+                    self.wl_fractional_manager.set(manager).unwrap();
+                }
+            }
+            self.create_fractional_scale(&queue.handle());
 
             let fd = connection
                 .prepare_read()
@@ -696,8 +1472,8 @@ pub mod imp {
                     if input.header.dwType == RIM_TYPEMOUSE.0 {
                         let (dx, dy) = (input.data.mouse.lLastX, input.data.mouse.lLastY);
                         let scale = this.obj().scale_factor() as f64;
-                        let (dx, dy) = (dx as f64 / scale, dy as f64 / scale);
-                        this.obj().emit_by_name::<()>("motion-relative", &[&dx, &dy]);
+                        let event = this.model.borrow().relative_motion(dx as f64 / scale, dy as f64 / scale);
+                        this.emit_input(event);
                     }
                 }
 
@@ -750,17 +1526,30 @@ pub mod imp {
             gl::BindVertexArray(0);
             self.texture_blit_vao.set(vao);
 
-            let tex_unit = gl::GetUniformLocation(
-                texture_blit_prg,
-                CString::new("tex_unit").unwrap().as_c_str().as_ptr(),
-            );
-            gl::ProgramUniform1i(texture_blit_prg, tex_unit, 0);
+            for prg in [texture_blit_prg, texture_blit_flip_prg] {
+                gl::ProgramUniform1i(prg, util::uniform_location(prg, "tex_unit"), 0);
+                gl::ProgramUniform1i(prg, util::uniform_location(prg, "tex_uv_unit"), 1);
+            }
 
             let mut tex_id = 0;
             gl::GenTextures(1, &mut tex_id);
             self.texture_id.set(tex_id);
+            let mut tex_id = 0;
+            gl::GenTextures(1, &mut tex_id);
+            self.texture_uv_id.set(tex_id);
+
+            // PBOs are core since GL 3.0 & GLES 3.0
+            let mut major = 0;
+            gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+            if major >= 3 && gl::MapBufferRange::is_loaded() {
+                let mut pbos = vec![0; PBO_RING_SIZE];
+                gl::GenBuffers(PBO_RING_SIZE as _, pbos.as_mut_ptr());
+                self.pbo_ring.replace(pbos);
+            } else {
+                log::info!("PBO not supported, using synchronous uploads");
+            }
 
-            self.obj().set_display_size(self.display_size.take());
+            self.allocate_texture();
             Ok(())
         }
 
@@ -805,7 +1594,7 @@ pub mod imp {
 
                 self.grabbed.set(self.grabbed.get() - Grab::MOUSE);
                 if !self.obj().mouse_absolute() {
-                    self.gl_area().set_cursor(None);
+                    self.set_widget_cursor(None);
                 }
                 self.obj().queue_draw(); // update cursor
                 self.obj().notify("grabbed");
@@ -814,14 +1603,200 @@ pub mod imp {
 
         fn key_press(&self, keyval: gdk::Key, keycode: u32) {
             self.keys_pressed.borrow_mut().insert((keyval, keycode));
-            self.obj()
-                .emit_by_name::<()>("key-event", &[&keyval, &keycode, &KeyEvent::PRESS]);
+            self.emit_input(InputEvent::Key {
+                keyval: keyval.into_glib(),
+                keycode,
+                event: KeyEvent::PRESS,
+            });
         }
 
         fn key_release(&self, keyval: gdk::Key, keycode: u32) {
             self.keys_pressed.borrow_mut().remove(&(keyval, keycode));
-            self.obj()
-                .emit_by_name::<()>("key-event", &[&keyval, &keycode, &KeyEvent::RELEASE])
+            self.emit_input(InputEvent::Key {
+                keyval: keyval.into_glib(),
+                keycode,
+                event: KeyEvent::RELEASE,
+            });
+        }
+
+        // the hardware keycode and shift level of a keyval, in the current layout
+        fn keyval_keycode(&self, keyval: gdk::Key) -> Option<(u32, i32)> {
+            let keys = gdk::traits::DisplayExt::map_keyval(&self.obj().display(), keyval)?;
+            keys.iter()
+                .filter(|k| k.group() == 0)
+                .min_by_key(|k| k.level())
+                .or_else(|| keys.first())
+                .map(|k| (k.keycode(), k.level()))
+        }
+
+        pub(crate) fn send_keys(&self, keyvals: &[gdk::Key]) {
+            self.emit_last_key_press();
+
+            let keys: Vec<_> = keyvals
+                .iter()
+                .filter_map(|&keyval| match self.keyval_keycode(keyval) {
+                    Some((keycode, _)) => Some((keyval, keycode)),
+                    None => {
+                        log::warn!("No keycode for {:?}", keyval.name());
+                        None
+                    }
+                })
+                .collect();
+            for &(keyval, keycode) in &keys {
+                self.key_press(keyval, keycode);
+            }
+            for &(keyval, keycode) in keys.iter().rev() {
+                self.key_release(keyval, keycode);
+            }
+        }
+
+        // the keys to press together for a keyval: modifiers of its level and itself
+        fn keyval_combo(&self, keyval: gdk::Key) -> Option<Vec<gdk::Key>> {
+            let (_, level) = self.keyval_keycode(keyval)?;
+            let mut keys = Vec::with_capacity(3);
+            if level & 1 != 0 {
+                keys.push(gdk::Key::Shift_L);
+            }
+            if level & 2 != 0 {
+                keys.push(gdk::Key::ISO_Level3_Shift);
+            }
+            keys.push(keyval);
+            Some(keys)
+        }
+
+        // the successive key combos to type a char, going through dead keys if needed
+        fn char_combos(&self, c: char) -> Vec<Vec<gdk::Key>> {
+            let keyval = match c {
+                '\n' => gdk::Key::Return,
+                '\t' => gdk::Key::Tab,
+                c => gdk::Key::from_unicode(c),
+            };
+            if let Some(keys) = self.keyval_combo(keyval) {
+                return vec![keys];
+            }
+
+            for (dead, composed, base) in DEAD_KEYS {
+                let Some(i) = composed.chars().position(|x| x == c) else {
+                    continue;
+                };
+                let base = base.chars().nth(i).unwrap();
+                if let (Some(dead), Some(base)) = (
+                    self.keyval_combo(*dead),
+                    self.keyval_combo(gdk::Key::from_unicode(base)),
+                ) {
+                    return vec![dead, base];
+                }
+            }
+
+            // a spacing accent, when only available as a dead key
+            let dead = match c {
+                '`' => Some(gdk::Key::dead_grave),
+                '\'' => Some(gdk::Key::dead_acute),
+                '^' => Some(gdk::Key::dead_circumflex),
+                '~' => Some(gdk::Key::dead_tilde),
+                '"' => Some(gdk::Key::dead_diaeresis),
+                _ => None,
+            };
+            if let Some((dead, space)) = dead
+                .and_then(|dead| self.keyval_combo(dead))
+                .zip(self.keyval_combo(gdk::Key::space))
+            {
+                return vec![dead, space];
+            }
+
+            log::warn!("Can't type {:?} with the current layout", c);
+            vec![]
+        }
+
+        fn type_char(&self, c: char) {
+            for keys in self.char_combos(c) {
+                self.send_keys(&keys);
+            }
+        }
+
+        pub(crate) fn send_text(&self, text: &str) {
+            for c in text.chars().filter(|&c| c != '\r') {
+                self.type_char(c);
+            }
+        }
+
+        pub(crate) fn type_text(&self, text: &str) {
+            self.cancel_typing();
+
+            let chars: Vec<char> = text.chars().filter(|&c| c != '\r').collect();
+            if chars.is_empty() {
+                return;
+            }
+            let source = glib::timeout_add_local(
+                Duration::from_millis(self.typing_delay.get().into()),
+                clone!(@weak self as this => @default-return glib::Continue(false), move || {
+                    this.type_next()
+                }),
+            );
+            self.typing.replace(Some(Typing {
+                chars,
+                pos: 0,
+                source: Some(source),
+            }));
+            if let Some((osd, progress)) = self.typing_osd.get() {
+                progress.set_fraction(0.0);
+                osd.set_visible(true);
+            }
+            self.obj().notify("typing");
+        }
+
+        fn type_next(&self) -> glib::Continue {
+            let (c, fraction, done) = {
+                let mut typing = self.typing.borrow_mut();
+                let Some(typing) = typing.as_mut() else {
+                    return glib::Continue(false);
+                };
+                let c = typing.chars[typing.pos];
+                typing.pos += 1;
+                let fraction = typing.pos as f64 / typing.chars.len() as f64;
+                (c, fraction, typing.pos == typing.chars.len())
+            };
+
+            self.type_char(c);
+            if let Some((_, progress)) = self.typing_osd.get() {
+                progress.set_fraction(fraction);
+            }
+            if done {
+                // the source is removed by returning false
+                self.typing.take();
+                self.typing_done();
+                return glib::Continue(false);
+            }
+            glib::Continue(true)
+        }
+
+        pub(crate) fn cancel_typing(&self) {
+            let Some(typing) = self.typing.take() else {
+                return;
+            };
+            if let Some(source) = typing.source {
+                source.remove();
+            }
+            self.typing_done();
+        }
+
+        fn typing_done(&self) {
+            if let Some((osd, _)) = self.typing_osd.get() {
+                osd.set_visible(false);
+            }
+            self.obj().notify("typing");
+        }
+
+        pub(crate) fn type_clipboard(&self) {
+            let clipboard = gdk::traits::DisplayExt::clipboard(&self.obj().display());
+            clipboard.read_text_async(
+                gio::Cancellable::NONE,
+                clone!(@weak self as this => move |res| match res {
+                    Ok(Some(text)) => this.type_text(&text),
+                    Ok(None) => log::debug!("No text in the clipboard"),
+                    Err(e) => log::warn!("Failed to read the clipboard text: {}", e),
+                }),
+            );
         }
 
         fn clear_last_key_press(&self) {
@@ -833,6 +1808,7 @@ pub mod imp {
 
         fn release_keys(&self) {
             self.clear_last_key_press();
+            self.keys_swallowed.borrow_mut().clear();
             for key in self.keys_pressed.take() {
                 self.key_release(key.0, key.1);
             }
@@ -858,6 +1834,11 @@ pub mod imp {
                         self.ungrab_mouse();
                     }
                 }
+                if self.zoom_shortcut(e) {
+                    self.emit_last_key_press();
+                    self.keys_swallowed.borrow_mut().insert(keycode);
+                    return;
+                }
             }
 
             // flush pending key event
@@ -876,18 +1857,19 @@ pub mod imp {
         }
 
         fn key_released(&self, keyval: gdk::Key, keycode: u32) {
+            if self.keys_swallowed.borrow_mut().remove(&keycode) {
+                return;
+            }
+
             if let Some((last_keyval, last_keycode)) = self.last_key_press.get() {
                 if (last_keyval, last_keycode) == (keyval, keycode) {
                     self.clear_last_key_press();
 
-                    self.obj().emit_by_name::<()>(
-                        "key-event",
-                        &[
-                            &keyval.into_glib(),
-                            &keycode,
-                            &(KeyEvent::PRESS | KeyEvent::RELEASE),
-                        ],
-                    );
+                    self.emit_input(InputEvent::Key {
+                        keyval: keyval.into_glib(),
+                        keycode,
+                        event: KeyEvent::PRESS | KeyEvent::RELEASE,
+                    });
                     return;
                 }
             }
@@ -1096,7 +2078,7 @@ pub mod imp {
                 grabbed |= Grab::MOUSE;
                 if !self.obj().mouse_absolute() {
                     // hide client mouse
-                    self.gl_area().set_cursor_from_name(Some("none"));
+                    self.set_widget_cursor(gdk::Cursor::from_name("none", None).as_ref());
                 }
                 self.obj().queue_draw(); // update cursor
             }
@@ -1105,158 +2087,1849 @@ pub mod imp {
             grabbed
         }
 
-        pub(crate) fn texture_id(&self) -> GLuint {
-            self.texture_id.get()
+        // returns false if the area is outside of the remote display
+        pub(crate) fn add_damage(&self, x: i32, y: i32, w: i32, h: i32) -> bool {
+            self.model.borrow_mut().add_damage(x, y, w, h)
         }
 
-        pub(crate) fn texture_blit(&self, flip: bool) {
-            unsafe {
-                gl::UseProgram(if flip {
-                    self.texture_blit_flip_prog.get()
-                } else {
-                    self.texture_blit_prog.get()
-                });
-                gl::ActiveTexture(gl::TEXTURE0);
-                gl::BindTexture(gl::TEXTURE_2D, self.texture_id());
-                gl::BindVertexArray(self.texture_blit_vao.get());
-                gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
-            }
+        // coalesce the damage accumulated since the last frame
+        pub(crate) fn take_damage(&self) -> Option<cairo::Region> {
+            let damage = self.model.borrow_mut().take_damage();
+            coalesce(damage.into_iter().map(Into::into).collect())
         }
 
-        fn borders(&self) -> (u32, u32) {
-            let obj = self.obj();
-            let (dw, dh) = match obj.display_size() {
-                Some(size) => size,
-                None => return (0, 0),
+        // copy the area to the framebuffer, and queue its upload
+        #[allow(clippy::too_many_arguments)]
+        pub(crate) fn update_area(
+            &self,
+            x: i32,
+            y: i32,
+            w: i32,
+            h: i32,
+            stride: i32,
+            format: PixelFormat,
+            data: &[u8],
+        ) {
+            let format_changed = self.format() != format;
+            let Some(area) = self
+                .model
+                .borrow_mut()
+                .update_area(x, y, w, h, stride, format, data)
+            else {
+                return;
             };
-            let sf = obj.scale_factor();
-            let (w, h) = (obj.width() * sf, obj.height() * sf);
-            let (sw, sh) = (w as f32 / dw as f32, h as f32 / dh as f32);
+            if format_changed {
+                self.texture_stale.set(true);
+            }
 
-            if sw < sh {
-                let bh = h - (h as f32 * sw / sh) as i32;
-                (0, bh as u32 / 2)
+            let area = gdk::Rectangle::from(area);
+            self.pending_uploads.borrow_mut().push(area);
+            #[cfg(unix)]
+            if self.dmabuf.take().is_some() {
+                // the texture is backed by the scanout, detach it
+                self.texture_stale.set(true);
+            }
+            self.queue_upload();
+        }
+
+        pub(crate) fn set_display_size(&self, size: Option<(usize, usize)>) {
+            if !self.model.borrow_mut().set_display_size(size) {
+                return;
+            }
+            self.configure_adjustments();
+            self.pending_uploads.borrow_mut().clear();
+
+            if self.software.get() {
+                self.texture_stale.set(true);
+                if let Some((width, height)) = size {
+                    let area = gdk::Rectangle::new(0, 0, width as _, height as _);
+                    self.pending_uploads.borrow_mut().push(area);
+                    self.queue_upload();
+                }
             } else {
-                let bw = w - (w as f32 * sh / sw) as i32;
-                (bw as u32 / 2, 0)
+                self.allocate_texture();
             }
+            self.obj().queue_resize();
         }
 
-        pub(crate) fn viewport(&self) -> Option<gdk::Rectangle> {
-            let obj = self.obj();
-            obj.display_size()?;
+        // (re)allocate the texture storage from the framebuffer
+        fn allocate_texture(&self) {
+            if !self.obj().is_realized() {
+                return;
+            }
+            let Some((width, height)) = self.display_size() else {
+                return;
+            };
 
-            let sf = obj.scale_factor();
-            let (w, h) = (obj.width() * sf, obj.height() * sf);
-            let (borderw, borderh) = self.borders();
-            let (borderw, borderh) = (borderw as i32, borderh as i32);
-            Some(gdk::Rectangle::new(
-                borderw,
-                borderh,
-                w - borderw * 2,
-                h - borderh * 2,
-            ))
+            let _ctx = self.make_current();
+            self.texture_stale.set(false);
+            let model = self.model.borrow();
+            let (internal, format, type_) = gl_format(model.format());
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, self.texture_id());
+                gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    internal as _,
+                    width as _,
+                    height as _,
+                    0,
+                    format,
+                    type_,
+                    model.framebuffer().as_ptr() as _,
+                );
+                gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            }
         }
 
-        // widget -> remote display pos
-        fn transform_pos(&self, x: f64, y: f64) -> Option<(f64, f64)> {
-            let obj = self.obj();
-            let sf = obj.scale_factor() as f64;
-            self.viewport().and_then(|vp| {
-                let (x, y) = (x * sf, y * sf);
-                if !vp.contains_point(x as _, y as _) {
-                    return None;
-                }
-                let (sw, sh) = obj.display_size().unwrap();
-                let x = (x - vp.x() as f64) * (sw as f64 / vp.width() as f64);
-                let y = (y - vp.y() as f64) * (sh as f64 / vp.height() as f64);
-                Some((x, y))
-            })
-        }
+        fn queue_upload(&self) {
+            if self.upload_tick_id.borrow().is_some() {
+                return;
+            }
 
-        // remote display pos -> widget pos
-        fn transform_pos_inv(&self, x: f64, y: f64) -> Option<(f64, f64)> {
-            let obj = self.obj();
-            let sf = obj.scale_factor() as f64;
-            self.viewport().map(|vp| {
-                let (sw, sh) = obj.display_size().unwrap();
-                let x = x * (vp.width() as f64 / sw as f64) + vp.x() as f64;
-                let y = y * (vp.height() as f64 / sh as f64) + vp.y() as f64;
-                (x / sf as f64, y / sf as f64)
-            })
+            let id = self.obj().add_tick_callback(|obj, clock| {
+                let imp = obj.imp();
+                if !imp.upload_due(clock) {
+                    return glib::Continue(true);
+                }
+                imp.upload_tick_id.replace(None);
+                imp.flush_uploads();
+                glib::Continue(false)
+            });
+            self.upload_tick_id.replace(Some(id));
         }
 
-        fn toplevel(&self) -> Option<gdk::Toplevel> {
-            let obj = self.obj();
-            obj.root()
-                .and_then(|r| r.native())
-                .map(|n| n.surface())
-                .and_then(|s| s.downcast::<gdk::Toplevel>().ok())
+        fn upload_due(&self, clock: &gdk::FrameClock) -> bool {
+            let max_fps = self.max_fps.get();
+            let now = clock.frame_time();
+            if max_fps > 0 {
+                let interval = 1_000_000 / max_fps as i64;
+                // allow some jitter, frame times are not exactly periodic
+                if let Some(last) = self.last_upload_time.get() {
+                    if now - last < interval - interval / 8 {
+                        return false;
+                    }
+                }
+            }
+            self.last_upload_time.set(Some(now));
+            true
         }
 
-        fn surface(&self) -> Option<gdk::Surface> {
-            let obj = self.obj();
-            obj.native().map(|n| n.surface())
+        pub(crate) fn flush_uploads(&self) {
+            let Some(region) = coalesce(self.pending_uploads.take()) else {
+                return;
+            };
+            let Some((dw, dh)) = self.display_size() else {
+                return;
+            };
+            if self.software.get() {
+                self.upload_software(&region);
+                return;
+            }
+            if !self.obj().is_realized() {
+                return;
+            }
+
+            let model = self.model.borrow();
+            let fb = model.framebuffer();
+            let format = model.format();
+            let (internal, gl_format, gl_type) = gl_format(format);
+            let bpp = format.bytes_per_pixel();
+            let uploaded = if self.texture_stale.get() {
+                vec![gdk::Rectangle::new(0, 0, dw as _, dh as _)]
+            } else {
+                rectangles(&region)
+            };
+            let _ctx = self.make_current();
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, self.texture_id());
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+                // 16bpp rows aren't 4-bytes aligned
+                gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+                if self.texture_stale.replace(false) {
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        internal as _,
+                        dw as _,
+                        dh as _,
+                        0,
+                        gl_format,
+                        gl_type,
+                        fb.as_ptr() as _,
+                    );
+                } else if !self.upload_pbo(&region, fb, dw) {
+                    gl::PixelStorei(gl::UNPACK_ROW_LENGTH, dw as _);
+                    for i in 0..region.num_rectangles() {
+                        let r = region.rectangle(i);
+                        let offset = (r.y() as usize * dw + r.x() as usize) * bpp;
+                        gl::TexSubImage2D(
+                            gl::TEXTURE_2D,
+                            0,
+                            r.x(),
+                            r.y(),
+                            r.width(),
+                            r.height(),
+                            gl_format,
+                            gl_type,
+                            fb[offset..].as_ptr() as _,
+                        );
+                    }
+                    gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+                }
+                gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            }
+            drop(model);
+            self.damage_uploaded(&uploaded);
+            self.gl_area().queue_render();
         }
 
-        #[cfg(windows)]
-        fn win32_handle(&self) -> Option<gdk_win32::HWND> {
-            self.surface()
-                .and_then(|s| s.downcast::<gdk_win32::Win32Surface>().ok())
-                .map(|w| w.handle())
+        // the damage is recorded once uploaded, for the next frame to show it
+        fn damage_uploaded(&self, rects: &[gdk::Rectangle]) {
+            for r in rects {
+                self.add_damage(r.x(), r.y(), r.width(), r.height());
+            }
         }
 
-        #[cfg(unix)]
-        fn egl_surface(&self) -> Option<egl::Surface> {
-            self.egl_surf.get().copied()
+        // schedule a new frame
+        pub(crate) fn queue_render(&self) {
+            if self.software.get() {
+                self.obj().queue_draw();
+            } else if let Some(area) = self.gl_area.get() {
+                area.queue_render();
+            }
         }
 
-        #[cfg(unix)]
-        fn wl_surface(&self) -> Option<wayland_client::protocol::wl_surface::WlSurface> {
-            self.surface()
-                .and_then(|s| s.downcast::<gdk_wl::WaylandSurface>().ok())
-                .map(|w| w.wl_surface().unwrap())
+        // the defined cursor, or the dot if hidden
+        pub(crate) fn shown_cursor(&self) -> Option<gdk::Cursor> {
+            let hidden = self
+                .model
+                .borrow()
+                .cursor()
+                .map_or(false, |c| c.is_transparent());
+            if hidden && self.cursor_dot.get() {
+                let dot = self.dot_cursor.get_or_init(|| {
+                    let (data, size) = super::dot_cursor_rgba();
+                    let hot = size / 2;
+                    super::Display::make_cursor(&data, size, size, hot, hot, 1)
+                });
+                return Some(dot.clone());
+            }
+            self.cursor.borrow().clone()
         }
 
-        #[cfg(unix)]
-        fn x11_xid(&self) -> Option<xlib::Window> {
-            self.surface()
-                .and_then(|s| s.downcast::<gdk_x11::X11Surface>().ok())
-                .map(|s| s.xid())
+        pub(crate) fn set_widget_cursor(&self, cursor: Option<&gdk::Cursor>) {
+            self.gl_area().set_cursor(cursor);
+            // the GL area is hidden when rendering in software
+            if self.software.get() {
+                self.obj().set_cursor(cursor);
+            }
         }
 
-        #[cfg(unix)]
-        pub(crate) fn egl_context(&self) -> Option<egl::Context> {
-            self.egl_display().and_then(|_| self.egl_ctx.get().copied())
+        fn update_software(&self) {
+            self.set_software(self.force_software.get() || self.gl_failed.get());
         }
 
-        #[cfg(unix)]
-        pub(crate) fn egl_display(&self) -> Option<egl::Display> {
-            let widget = self.obj();
+        fn set_software(&self, software: bool) {
+            if self.software.replace(software) == software {
+                return;
+            }
+            log::info!("Software rendering: {}", software);
 
-            if let Ok(dpy) = widget.display().downcast::<gdk_wl::WaylandDisplay>() {
-                return dpy.egl_display();
+            if let Some(area) = self.gl_area.get() {
+                area.set_visible(!software);
+                let cursor = if software { area.cursor() } else { None };
+                self.obj().set_cursor(cursor.as_ref());
+            }
+            self.sw_pixels.replace(Vec::new());
+            self.sw_texture.replace(None);
+            self.last_frame.set(None);
+            #[cfg(unix)]
+            if let Some(s) = self.dmabuf.take() {
+                if software {
+                    self.download_dmabuf(s);
+                }
             }
+            // upload everything again, to the texture or the CPU copy
+            self.texture_stale.set(true);
+            if let Some((w, h)) = self.display_size() {
+                let area = gdk::Rectangle::new(0, 0, w as _, h as _);
+                self.pending_uploads.borrow_mut().push(area);
+                self.queue_upload();
+            }
+            self.queue_render();
+            self.obj().notify("software-rendering");
+        }
 
-            if let Ok(dpy) = widget.display().downcast::<gdk_x11::X11Display>() {
-                return dpy.egl_display();
+        // convert the framebuffer region to the software texture pixels
+        fn upload_software(&self, region: &cairo::Region) {
+            let Some((dw, dh)) = self.display_size() else {
+                return;
             };
+            let model = self.model.borrow();
+            let fb = model.framebuffer();
+            let format = model.format();
+            let bpp = format.bytes_per_pixel();
+            let mut pixels = self.sw_pixels.borrow_mut();
+
+            let mut rects = rectangles(region);
+            if self.texture_stale.replace(false) || pixels.len() != dw * dh * 4 {
+                pixels.resize(dw * dh * 4, 0);
+                rects = vec![gdk::Rectangle::new(0, 0, dw as _, dh as _)];
+            }
+            for r in &rects {
+                let (x, w) = (r.x() as usize, r.width() as usize);
+                for y in r.y() as usize..(r.y() + r.height()) as usize {
+                    let src = (y * dw + x) * bpp;
+                    let dst = (y * dw + x) * 4;
+                    match (fb.get(src..src + w * bpp), pixels.get_mut(dst..dst + w * 4)) {
+                        (Some(src), Some(dst)) => format.to_bgra_premultiplied(src, dst),
+                        _ => break,
+                    }
+                }
+            }
 
-            None
+            drop(pixels);
+            drop(model);
+            self.damage_uploaded(&rects);
+            self.sw_texture.replace(None);
+            self.obj().queue_draw();
         }
-    }
-}
 
-impl Display {
-    pub fn make_cursor(
-        data: &[u8],
-        width: i32,
-        height: i32,
-        hot_x: i32,
-        hot_y: i32,
-        scale: i32,
-    ) -> gdk::Cursor {
-        assert!(scale > 0);
+        pub(crate) fn software_texture(&self) -> Option<gdk::Texture> {
+            if let Some(texture) = &*self.sw_texture.borrow() {
+                return Some(texture.clone());
+            }
+            let (dw, dh) = self.display_size()?;
+            let pixels = self.sw_pixels.borrow();
+            if pixels.len() != dw * dh * 4 {
+                return None;
+            }
+            let texture = gdk::MemoryTexture::new(
+                dw as _,
+                dh as _,
+                gdk::MemoryFormat::B8g8r8a8Premultiplied,
+                &glib::Bytes::from(&pixels[..]),
+                dw * 4,
+            )
+            .upcast::<gdk::Texture>();
+            self.sw_texture.replace(Some(texture.clone()));
+            Some(texture)
+        }
+
+        // the remote display content, at its native resolution
+        pub(crate) fn snapshot_texture(&self) -> Option<gdk::Texture> {
+            #[cfg(unix)]
+            if self.dmabuf.borrow().is_some() {
+                return self.read_dmabuf_texture();
+            }
+            self.framebuffer_texture()
+        }
+
+        fn framebuffer_texture(&self) -> Option<gdk::Texture> {
+            let (dw, dh) = self.display_size()?;
+            let pixels = self.model.borrow().to_bgra_premultiplied();
+            Some(
+                gdk::MemoryTexture::new(
+                    dw as _,
+                    dh as _,
+                    gdk::MemoryFormat::B8g8r8a8Premultiplied,
+                    &glib::Bytes::from_owned(pixels),
+                    dw * 4,
+                )
+                .upcast(),
+            )
+        }
+
+        #[cfg(unix)]
+        fn read_dmabuf_texture(&self) -> Option<gdk::Texture> {
+            let (w, h) = self.content_size()?;
+            let pixels = self.read_dmabuf_pixels(&gdk::Rectangle::new(0, 0, w as _, h as _))?;
+            Some(
+                gdk::MemoryTexture::new(
+                    w as _,
+                    h as _,
+                    gdk::MemoryFormat::R8g8b8a8Premultiplied,
+                    &glib::Bytes::from_owned(pixels),
+                    w * 4,
+                )
+                .upcast(),
+            )
+        }
+
+        // the size of snapshot_texture()
+        pub(crate) fn content_size(&self) -> Option<(usize, usize)> {
+            #[cfg(unix)]
+            if let Some(d) = &*self.dmabuf.borrow() {
+                return Some((d.width as _, d.height as _));
+            }
+            self.display_size()
+        }
+
+        // copy an area of snapshot_texture() to a whole BGRA premultiplied frame
+        pub(crate) fn read_area(
+            &self,
+            area: &gdk::Rectangle,
+            dst: &mut [u8],
+            stride: usize,
+        ) -> bool {
+            let (x, y) = (area.x() as usize, area.y() as usize);
+            let (w, h) = (area.width() as usize, area.height() as usize);
+            if w == 0 || h == 0 {
+                return true;
+            }
+            let row_offset = |row: usize| (y + row) * stride + x * 4;
+
+            #[cfg(unix)]
+            if self.dmabuf.borrow().is_some() {
+                let Some(pixels) = self.read_dmabuf_pixels(area) else {
+                    return false;
+                };
+                for (row, src) in pixels.chunks_exact(w * 4).enumerate() {
+                    let offset = row_offset(row);
+                    let Some(dst) = dst.get_mut(offset..offset + w * 4) else {
+                        return false;
+                    };
+                    for (d, s) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+                        d.copy_from_slice(&[s[2], s[1], s[0], s[3]]);
+                    }
+                }
+                return true;
+            }
+
+            let Some((dw, _)) = self.display_size() else {
+                return false;
+            };
+            let model = self.model.borrow();
+            let format = model.format();
+            let bpp = format.bytes_per_pixel();
+            for row in 0..h {
+                let src = ((y + row) * dw + x) * bpp;
+                let offset = row_offset(row);
+                match (
+                    model.framebuffer().get(src..src + w * bpp),
+                    dst.get_mut(offset..offset + w * 4),
+                ) {
+                    (Some(src), Some(dst)) => format.to_bgra_premultiplied(src, dst),
+                    _ => return false,
+                }
+            }
+            true
+        }
+
+        // blit the scanout to an offscreen texture, and read the area back, in RGBA
+        #[cfg(unix)]
+        fn read_dmabuf_pixels(&self, area: &gdk::Rectangle) -> Option<Vec<u8>> {
+            let (w, h, flip) = match &*self.dmabuf.borrow() {
+                Some(d) => (d.width as usize, d.height as usize, d.y0_top),
+                None => return None,
+            };
+            let mut pixels = vec![0u8; area.width() as usize * area.height() as usize * 4];
+
+            let _ctx = self.make_current();
+            let complete = unsafe {
+                // may be called while rendering to the GL area framebuffer
+                let (mut draw_fbo, mut read_fbo) = (0, 0);
+                let mut viewport = [0; 4];
+                gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut draw_fbo);
+                gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut read_fbo);
+                gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+
+                let (mut fbo, mut tex) = (0, 0);
+                gl::GenTextures(1, &mut tex);
+                gl::BindTexture(gl::TEXTURE_2D, tex);
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::RGBA8 as _,
+                    w as _,
+                    h as _,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    std::ptr::null(),
+                );
+                gl::GenFramebuffers(1, &mut fbo);
+                gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_2D,
+                    tex,
+                    0,
+                );
+                let complete =
+                    gl::CheckFramebufferStatus(gl::FRAMEBUFFER) == gl::FRAMEBUFFER_COMPLETE;
+                if complete {
+                    gl::Viewport(0, 0, w as _, h as _);
+                    gl::Disable(gl::BLEND);
+                    // upside down, so rows are read back top to bottom
+                    self.texture_blit_with_transform(flip, &[1.0, 0.0, 0.0, -1.0]);
+                    gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+                    gl::ReadPixels(
+                        area.x(),
+                        area.y(),
+                        area.width(),
+                        area.height(),
+                        gl::RGBA,
+                        gl::UNSIGNED_BYTE,
+                        pixels.as_mut_ptr() as _,
+                    );
+                }
+                gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, draw_fbo as _);
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, read_fbo as _);
+                gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+                gl::DeleteFramebuffers(1, &fbo);
+                gl::DeleteTextures(1, &tex);
+                complete
+            };
+            if !complete {
+                log::warn!("Failed to setup the readback framebuffer");
+                return None;
+            }
+            Some(pixels)
+        }
+
+        // the software equivalent of render()
+        fn snapshot_software(&self, snapshot: &gtk::Snapshot) {
+            let obj = self.obj();
+            let bounds = graphene::Rect::new(0.0, 0.0, obj.width() as _, obj.height() as _);
+            snapshot.append_color(&gdk::RGBA::new(0.1, 0.1, 0.1, 1.0), &bounds);
+
+            if let (Some((dw, dh)), Some((ow, oh)), Some(vp), Some(texture)) = (
+                self.display_size(),
+                self.oriented_size(),
+                self.viewport(),
+                self.software_texture(),
+            ) {
+                let sf = obj.scale_factor() as f32;
+                let (dw, dh) = (dw as f32, dh as f32);
+                let (ow, oh) = (ow as f32, oh as f32);
+
+                snapshot.push_clip(&bounds);
+                snapshot.save();
+                snapshot.translate(&graphene::Point::new(
+                    vp.x() as f32 / sf,
+                    vp.y() as f32 / sf,
+                ));
+                snapshot.scale(vp.width() as f32 / sf / ow, vp.height() as f32 / sf / oh);
+                // same as orient()
+                if self.flip_horizontal() {
+                    snapshot.translate(&graphene::Point::new(ow, 0.0));
+                    snapshot.scale(-1.0, 1.0);
+                }
+                if self.flip_vertical() {
+                    snapshot.translate(&graphene::Point::new(0.0, oh));
+                    snapshot.scale(1.0, -1.0);
+                }
+                let (offset, angle) = match self.rotation() {
+                    Rotation::Rotate90 => ((dh, 0.0), 90.0),
+                    Rotation::Rotate180 => ((dw, dh), 180.0),
+                    Rotation::Rotate270 => ((0.0, dw), 270.0),
+                    _ => ((0.0, 0.0), 0.0),
+                };
+                snapshot.translate(&graphene::Point::new(offset.0, offset.1));
+                snapshot.rotate(angle);
+                snapshot.append_texture(&texture, &graphene::Rect::new(0.0, 0.0, dw, dh));
+                snapshot.restore();
+                snapshot.pop();
+            }
+
+            if let Some(damage) = self.take_damage() {
+                obj.emit_by_name::<()>("frame-damage", &[&damage]);
+            }
+        }
+
+        // copy a linear RGB scanout to the framebuffer, as there is no GL to import it
+        #[cfg(unix)]
+        pub(crate) fn download_dmabuf(&self, mut s: RdwDmabufScanout) {
+            let format = match s.fourcc {
+                egl::DRM_FORMAT_XRGB8888 => Some(PixelFormat::Xrgb8888),
+                egl::DRM_FORMAT_ARGB8888 => Some(PixelFormat::Argb8888Premultiplied),
+                egl::DRM_FORMAT_XBGR8888 => Some(PixelFormat::Xbgr8888),
+                egl::DRM_FORMAT_ABGR8888 => Some(PixelFormat::Abgr8888Premultiplied),
+                _ => None,
+            };
+            let plane = s.planes[0];
+            let linear = matches!(
+                plane.modifier,
+                egl::DRM_FORMAT_MOD_LINEAR | egl::DRM_FORMAT_MOD_INVALID
+            );
+
+            match format {
+                Some(format) if s.n_planes == 1 && linear => {
+                    if s.acquire_fd >= 0 {
+                        util::wait_fence(std::mem::replace(&mut s.acquire_fd, -1));
+                    }
+                    let (stride, height) = (plane.stride as usize, s.height as usize);
+                    let size = plane.offset as usize + stride * height;
+                    unsafe {
+                        let ptr = libc::mmap(
+                            std::ptr::null_mut(),
+                            size,
+                            libc::PROT_READ,
+                            libc::MAP_SHARED,
+                            plane.fd,
+                            0,
+                        );
+                        if ptr == libc::MAP_FAILED {
+                            log::warn!(
+                                "Failed to mmap DMABUF: {}",
+                                std::io::Error::last_os_error()
+                            );
+                        } else {
+                            let sync = |flags: u64| {
+                                let flags = egl::DMA_BUF_SYNC_READ | flags;
+                                libc::ioctl(plane.fd, egl::DMA_BUF_IOCTL_SYNC as _, &flags)
+                            };
+                            sync(egl::DMA_BUF_SYNC_START);
+                            let data = std::slice::from_raw_parts(ptr as *const u8, size);
+                            let data = &data[plane.offset as usize..];
+                            if s.y0_top {
+                                let flipped: Vec<u8> =
+                                    data.chunks_exact(stride).rev().flatten().copied().collect();
+                                self.update_area(
+                                    0,
+                                    0,
+                                    s.width as _,
+                                    s.height as _,
+                                    stride as _,
+                                    format,
+                                    &flipped,
+                                );
+                            } else {
+                                self.update_area(
+                                    0,
+                                    0,
+                                    s.width as _,
+                                    s.height as _,
+                                    stride as _,
+                                    format,
+                                    data,
+                                );
+                            }
+                            sync(egl::DMA_BUF_SYNC_END);
+                            libc::munmap(ptr, size);
+                        }
+                    }
+                }
+                _ => log::warn!(
+                    "Can't display DMABUF scanout without GL: {:?}",
+                    (s.fourcc, s.n_planes, plane.modifier)
+                ),
+            }
+
+            drop(s);
+            self.emit_dmabuf_released();
+        }
+
+        // stage the region in the next PBO, and let the driver upload it asynchronously
+        unsafe fn upload_pbo(&self, region: &cairo::Region, fb: &[u8], dw: usize) -> bool {
+            let pbo = {
+                let ring = self.pbo_ring.borrow();
+                if ring.is_empty() {
+                    return false;
+                }
+                let index = self.pbo_index.get();
+                self.pbo_index.set((index + 1) % ring.len());
+                ring[index]
+            };
+
+            let format = self.format();
+            let (_, gl_format, gl_type) = gl_format(format);
+            let bpp = format.bytes_per_pixel();
+            let rects: Vec<_> = (0..region.num_rectangles())
+                .map(|i| region.rectangle(i))
+                .collect();
+            let size: usize = rects
+                .iter()
+                .map(|r| r.width() as usize * r.height() as usize * bpp)
+                .sum();
+
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, pbo);
+            // orphan the previous storage, it may still be in use
+            gl::BufferData(
+                gl::PIXEL_UNPACK_BUFFER,
+                size as _,
+                std::ptr::null(),
+                gl::STREAM_DRAW,
+            );
+            let ptr = gl::MapBufferRange(
+                gl::PIXEL_UNPACK_BUFFER,
+                0,
+                size as _,
+                gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_BUFFER_BIT,
+            ) as *mut u8;
+            if ptr.is_null() {
+                log::warn!("Failed to map PBO");
+                gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+                return false;
+            }
+
+            let staging = std::slice::from_raw_parts_mut(ptr, size);
+            let mut offset = 0;
+            let mut offsets = Vec::with_capacity(rects.len());
+            for r in &rects {
+                let len = r.width() as usize * bpp;
+                offsets.push(offset);
+                for row in 0..r.height() as usize {
+                    let src = ((r.y() as usize + row) * dw + r.x() as usize) * bpp;
+                    staging[offset..offset + len].copy_from_slice(&fb[src..src + len]);
+                    offset += len;
+                }
+            }
+            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+
+            for (r, offset) in rects.iter().zip(offsets) {
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    r.x(),
+                    r.y(),
+                    r.width(),
+                    r.height(),
+                    gl_format,
+                    gl_type,
+                    offset as _,
+                );
+            }
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+            true
+        }
+
+        pub(crate) fn texture_id(&self) -> GLuint {
+            self.texture_id.get()
+        }
+
+        pub(crate) fn texture_blit(&self, flip: bool) {
+            self.texture_blit_with_transform(flip, &self.blit_transform());
+        }
+
+        fn texture_blit_with_transform(&self, flip: bool, transform: &[f32; 4]) {
+            // keep the pixels crisp when scaling by integer factors
+            let mag_filter = match self.scaling_mode() {
+                ScalingMode::Original | ScalingMode::IntegerScale => gl::NEAREST,
+                _ => gl::LINEAR,
+            };
+            let prog = if flip {
+                self.texture_blit_flip_prog.get()
+            } else {
+                self.texture_blit_prog.get()
+            };
+            let yuv = self.blit_yuv();
+            unsafe {
+                gl::UseProgram(prog);
+                gl::UniformMatrix2fv(
+                    util::uniform_location(prog, "transform"),
+                    1,
+                    gl::FALSE,
+                    transform.as_ptr(),
+                );
+                let alpha_mode = self.blit_alpha_mode();
+                gl::Uniform1i(util::uniform_location(prog, "alpha_mode"), alpha_mode);
+                if alpha_mode != 0 {
+                    // blend over the background
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+                }
+                gl::Uniform1i(util::uniform_location(prog, "yuv"), yuv as _);
+                if yuv {
+                    gl::ActiveTexture(gl::TEXTURE1);
+                    gl::BindTexture(gl::TEXTURE_2D, self.texture_uv_id.get());
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+                }
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.texture_id());
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as _);
+                gl::BindVertexArray(self.texture_blit_vao.get());
+                gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+            }
+        }
+
+        // widget -> texture coordinates matrix (column-major), undoing flips then rotation
+        fn blit_transform(&self) -> [f32; 4] {
+            let (r00, r01, r10, r11) = match self.rotation() {
+                Rotation::Rotate90 => (0.0, 1.0, -1.0, 0.0),
+                Rotation::Rotate180 => (-1.0, 0.0, 0.0, -1.0),
+                Rotation::Rotate270 => (0.0, -1.0, 1.0, 0.0),
+                _ => (1.0, 0.0, 0.0, 1.0),
+            };
+            let fx = if self.flip_horizontal() { -1.0 } else { 1.0 };
+            let fy = if self.flip_vertical() { -1.0 } else { 1.0 };
+            // GL y axis points up, while rotation is defined with y down
+            [r00 * fx, -r10 * fx, -r01 * fy, r11 * fy]
+        }
+
+        // whether the GL context and resources are there to render
+        pub(crate) fn gl_realized(&self) -> bool {
+            #[cfg(unix)]
+            if self.egl_surface().is_some() {
+                return self.obj().is_realized();
+            }
+            self.gl_area.get().map_or(false, |area| area.is_realized())
+        }
+
+        pub(crate) fn frame_state(&self, flip: bool) -> FrameState {
+            let obj = self.obj();
+            let sf = obj.scale_factor();
+            let mut target = (0, 0);
+            unsafe {
+                let mut fbo = 0;
+                gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut fbo);
+                if fbo != 0 {
+                    let mut attachment = 0;
+                    gl::GetFramebufferAttachmentParameteriv(
+                        gl::DRAW_FRAMEBUFFER,
+                        gl::COLOR_ATTACHMENT0,
+                        gl::FRAMEBUFFER_ATTACHMENT_OBJECT_NAME,
+                        &mut attachment,
+                    );
+                    target = (fbo as _, attachment as _);
+                }
+            }
+            FrameState {
+                size: (obj.width() * sf, obj.height() * sf),
+                viewport: self
+                    .viewport()
+                    .map(|vp| (vp.x(), vp.y(), vp.width(), vp.height())),
+                transform: self.blit_transform(),
+                flip,
+                scaling_mode: self.scaling_mode(),
+                target,
+            }
+        }
+
+        // whether the render target still holds the last frame, drawn the same way
+        pub(crate) fn frame_unchanged(&self, state: &FrameState) -> bool {
+            let last = self.last_frame.replace(Some(*state));
+            // the default framebuffer content is undefined after a swap
+            state.target.1 != 0 && last.as_ref() == Some(state)
+        }
+
+        // the damaged area as GL scissor boxes, in window coordinates
+        pub(crate) fn damage_scissors(
+            &self,
+            damage: &cairo::Region,
+            vp: &gdk::Rectangle,
+        ) -> Vec<(i32, i32, i32, i32)> {
+            let Some((ow, oh)) = self.oriented_size() else {
+                return vec![];
+            };
+            let height = self.obj().height() * self.obj().scale_factor();
+            let (sx, sy) = (
+                vp.width() as f64 / ow as f64,
+                vp.height() as f64 / oh as f64,
+            );
+            (0..damage.num_rectangles())
+                .filter_map(|i| {
+                    let r = damage.rectangle(i);
+                    let (x0, y0) = self.orient(r.x() as _, r.y() as _)?;
+                    let (x1, y1) =
+                        self.orient((r.x() + r.width()) as _, (r.y() + r.height()) as _)?;
+                    // a pixel of margin for the linear filtering
+                    let left = (x0.min(x1) * sx).floor() as i32 + vp.x() - 1;
+                    let top = (y0.min(y1) * sy).floor() as i32 + vp.y() - 1;
+                    let right = (x0.max(x1) * sx).ceil() as i32 + vp.x() + 1;
+                    let bottom = (y0.max(y1) * sy).ceil() as i32 + vp.y() + 1;
+                    Some((left, height - bottom, right - left, bottom - top))
+                })
+                .collect()
+        }
+
+        // whether the texture holds the Y plane, and the UV plane is in texture_uv_id
+        fn blit_yuv(&self) -> bool {
+            #[cfg(unix)]
+            if let Some(d) = &*self.dmabuf.borrow() {
+                return d.planes().len() >= 2
+                    && matches!(d.fourcc, egl::DRM_FORMAT_NV12 | egl::DRM_FORMAT_P010);
+            }
+            false
+        }
+
+        fn blit_alpha_mode(&self) -> GLint {
+            #[cfg(unix)]
+            if self.dmabuf.borrow().is_some() {
+                return 0;
+            }
+            alpha_mode(self.format())
+        }
+
+        pub(crate) fn display_size(&self) -> Option<(usize, usize)> {
+            self.model.borrow().display_size()
+        }
+
+        pub(crate) fn format(&self) -> PixelFormat {
+            self.model.borrow().format()
+        }
+
+        fn scaling_mode(&self) -> ScalingMode {
+            self.model.borrow().scaling_mode()
+        }
+
+        fn rotation(&self) -> Rotation {
+            self.model.borrow().rotation()
+        }
+
+        fn flip_horizontal(&self) -> bool {
+            self.model.borrow().flip_horizontal()
+        }
+
+        fn flip_vertical(&self) -> bool {
+            self.model.borrow().flip_vertical()
+        }
+
+        fn zoom_level(&self) -> f64 {
+            self.model.borrow().zoom_level()
+        }
+
+        fn cursor_position(&self) -> Option<(usize, usize)> {
+            self.model.borrow().cursor_position()
+        }
+
+        // the widget area, as seen by the model
+        pub(crate) fn geometry(&self) -> Geometry {
+            let obj = self.obj();
+            let value =
+                |adj: &RefCell<Option<gtk::Adjustment>>| adj.borrow().as_ref().map(|a| a.value());
+            Geometry {
+                width: obj.width(),
+                height: obj.height(),
+                scale_factor: obj.scale_factor(),
+                scroll: (value(&self.hadjustment), value(&self.vadjustment)),
+            }
+        }
+
+        fn is_rotated(&self) -> bool {
+            self.model.borrow().is_rotated()
+        }
+
+        fn oriented_size(&self) -> Option<(u32, u32)> {
+            self.model.borrow().oriented_size()
+        }
+
+        fn orient(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+            self.model.borrow().orient(x, y)
+        }
+
+        fn orientation_changed(&self) {
+            self.configure_adjustments();
+            self.obj().queue_resize();
+            self.queue_render();
+            // the guest may want to follow the new aspect
+            self.last_resize_request.set(None);
+        }
+
+        fn display_scale(&self) -> Option<(f64, f64)> {
+            let geometry = self.geometry();
+            self.model.borrow().display_scale(&geometry)
+        }
+
+        // the remote display area, in widget device pixels (may exceed the widget)
+        pub(crate) fn viewport(&self) -> Option<gdk::Rectangle> {
+            let geometry = self.geometry();
+            self.model.borrow().viewport(&geometry).map(Into::into)
+        }
+
+        fn content_size(&self) -> Option<(f64, f64)> {
+            let geometry = self.geometry();
+            self.model.borrow().content_size(&geometry)
+        }
+
+        fn is_scrolled(&self) -> bool {
+            self.obj()
+                .parent()
+                .map_or(false, |p| p.is::<gtk::ScrolledWindow>())
+        }
+
+        fn set_adjustment(&self, orientation: gtk::Orientation, adj: Option<gtk::Adjustment>) {
+            let (adjustment, handler_id) = match orientation {
+                gtk::Orientation::Horizontal => (&self.hadjustment, &self.hadjustment_id),
+                _ => (&self.vadjustment, &self.vadjustment_id),
+            };
+
+            if let (Some(old), Some(id)) = (adjustment.borrow().as_ref(), handler_id.take()) {
+                old.disconnect(id);
+            }
+
+            // like GtkViewport, use our own adjustment if none is given
+            let adj = adj.unwrap_or_else(|| gtk::Adjustment::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0));
+            let id = adj.connect_value_changed(clone!(@weak self as this => move |_| {
+                this.scrolled();
+            }));
+            handler_id.set(Some(id));
+            adjustment.replace(Some(adj));
+
+            self.configure_adjustments();
+        }
+
+        pub(crate) fn configure_adjustments(&self) {
+            let obj = self.obj();
+            let (w, h) = (obj.width() as f64, obj.height() as f64);
+            let (cw, ch) = self.content_size().unwrap_or((0.0, 0.0));
+
+            let configure = |adj: &gtk::Adjustment, content: f64, page: f64| {
+                let upper = content.max(page);
+                let value = if adj.upper() <= adj.page_size() {
+                    // it wasn't scrollable, start from the center
+                    (upper - page) / 2.0
+                } else {
+                    adj.value()
+                };
+                adj.configure(
+                    value.clamp(0.0, upper - page),
+                    0.0,
+                    upper,
+                    page * 0.1,
+                    page * 0.9,
+                    page,
+                );
+            };
+
+            if let Some(adj) = self.hadjustment.borrow().clone() {
+                configure(&adj, cw, w);
+            }
+            if let Some(adj) = self.vadjustment.borrow().clone() {
+                configure(&adj, ch, h);
+            }
+        }
+
+        fn scrolled(&self) {
+            self.queue_render();
+            self.obj().queue_draw(); // update cursor
+        }
+
+        fn scroll_by(&self, dx: f64, dy: f64) {
+            if let Some(adj) = self.hadjustment.borrow().clone() {
+                adj.set_value(adj.value() + dx);
+            }
+            if let Some(adj) = self.vadjustment.borrow().clone() {
+                adj.set_value(adj.value() + dy);
+            }
+        }
+
+        // widget position of the pointer, as seen by the remote
+        fn pointer_position(&self) -> Option<(f64, f64)> {
+            if !self.obj().mouse_absolute() && self.grabbed.get().contains(Grab::MOUSE) {
+                self.cursor_position
+                    .get()
+                    .and_then(|(x, y)| self.transform_pos_inv(x as _, y as _))
+            } else {
+                self.last_pointer.get()
+            }
+        }
+
+        fn edge_pan_delta(&self) -> (f64, f64) {
+            if !self.edge_pan.get() || self.grabbed.get().is_empty() {
+                return (0.0, 0.0);
+            }
+            let Some((x, y)) = self.pointer_position() else {
+                return (0.0, 0.0);
+            };
+
+            let obj = self.obj();
+            let delta = |pos: f64, size: f64| {
+                if pos < EDGE_PAN_BORDER {
+                    (pos - EDGE_PAN_BORDER).max(-EDGE_PAN_BORDER)
+                } else if pos > size - EDGE_PAN_BORDER {
+                    (pos - (size - EDGE_PAN_BORDER)).min(EDGE_PAN_BORDER)
+                } else {
+                    0.0
+                }
+            };
+            (delta(x, obj.width() as f64), delta(y, obj.height() as f64))
+        }
+
+        pub(crate) fn update_edge_pan(&self) {
+            if self.edge_pan_delta() == (0.0, 0.0) {
+                if let Some(id) = self.edge_pan_id.take() {
+                    id.remove();
+                }
+                return;
+            }
+
+            let id = self.edge_pan_id.take();
+            if id.is_some() {
+                self.edge_pan_id.set(id);
+                return;
+            }
+
+            self.edge_pan_id.set(Some(glib::timeout_add_local(
+                Duration::from_millis(16),
+                clone!(@weak self as this => @default-return glib::Continue(false), move || {
+                    let (dx, dy) = this.edge_pan_delta();
+                    if (dx, dy) == (0.0, 0.0) {
+                        this.edge_pan_id.set(None);
+                        return glib::Continue(false);
+                    }
+
+                    this.scroll_by(dx, dy);
+                    // the view moved under the pointer
+                    if this.obj().mouse_absolute() {
+                        if let Some(event) = this.last_pointer.get().and_then(|(x, y)| this.pointer_motion(x, y)) {
+                            this.emit_input(event);
+                        }
+                    }
+                    glib::Continue(true)
+                }),
+            )));
+        }
+
+        fn set_zoom_level(&self, level: f64, center: Option<(f64, f64)>) {
+            let level = level.clamp(ZOOM_MIN, ZOOM_MAX);
+            if self.zoom_level() == level {
+                return;
+            }
+
+            // keep the remote position under the pointer (or center) in place
+            let obj = self.obj();
+            let center = center
+                .or_else(|| self.last_pointer.get())
+                .unwrap_or((obj.width() as f64 / 2.0, obj.height() as f64 / 2.0));
+            let pos = self
+                .transform_pos_unclamped(center.0, center.1)
+                .and_then(|(x, y)| self.orient(x, y));
+
+            self.model.borrow_mut().set_zoom_level(level);
+            self.configure_adjustments();
+
+            if let (Some((x, y)), Some((sw, sh))) = (pos, self.display_scale()) {
+                let sf = obj.scale_factor() as f64;
+                if let Some(adj) = self.hadjustment.borrow().clone() {
+                    adj.set_value(x * sw / sf - center.0);
+                }
+                if let Some(adj) = self.vadjustment.borrow().clone() {
+                    adj.set_value(y * sh / sf - center.1);
+                }
+            }
+
+            obj.queue_resize();
+            self.scrolled();
+        }
+
+        pub(crate) fn zoom_by(&self, factor: f64) {
+            self.set_zoom_level(self.zoom_level() * factor, None);
+            self.obj().notify("zoom-level");
+        }
+
+        fn zoom_shortcut(&self, event: &gdk::Event) -> bool {
+            let matches = |shortcut: &RefCell<Option<gtk::ShortcutTrigger>>| {
+                shortcut
+                    .borrow()
+                    .as_ref()
+                    .map_or(false, |s| s.trigger(event, false) == gdk::KeyMatch::Exact)
+            };
+
+            if matches(&self.zoom_in_shortcut) {
+                self.zoom_by(ZOOM_STEP);
+            } else if matches(&self.zoom_out_shortcut) {
+                self.zoom_by(1.0 / ZOOM_STEP);
+            } else if matches(&self.zoom_reset_shortcut) {
+                self.zoom_by(1.0 / self.zoom_level());
+            } else {
+                return false;
+            }
+            true
+        }
+
+        fn pointer_motion(&self, x: f64, y: f64) -> Option<InputEvent> {
+            let geometry = self.geometry();
+            self.model.borrow().pointer_motion(&geometry, x, y)
+        }
+
+        // emit the input event signal, the model must not be borrowed
+        pub(crate) fn emit_input(&self, event: InputEvent) {
+            let obj = self.obj();
+            match event {
+                InputEvent::Key {
+                    keyval,
+                    keycode,
+                    event,
+                } => obj.emit_by_name::<()>("key-event", &[&keyval, &keycode, &event]),
+                InputEvent::Motion { x, y } => obj.emit_by_name::<()>("motion", &[&x, &y]),
+                InputEvent::MotionRelative { dx, dy } => {
+                    obj.emit_by_name::<()>("motion-relative", &[&dx, &dy])
+                }
+                InputEvent::MousePress(button) => obj.emit_by_name::<()>("mouse-press", &[&button]),
+                InputEvent::MouseRelease(button) => {
+                    obj.emit_by_name::<()>("mouse-release", &[&button])
+                }
+                InputEvent::ScrollDiscrete(scroll) => {
+                    obj.emit_by_name::<()>("scroll-discrete", &[&scroll])
+                }
+                InputEvent::ScrollSmooth {
+                    dx,
+                    dy,
+                    v120_x,
+                    v120_y,
+                } => obj.emit_by_name::<()>("scroll-smooth", &[&dx, &dy, &v120_x, &v120_y]),
+                InputEvent::Stylus {
+                    x,
+                    y,
+                    pressure,
+                    tilt_x,
+                    tilt_y,
+                    rotation,
+                    flags,
+                } => obj.emit_by_name::<()>(
+                    "stylus",
+                    &[&x, &y, &pressure, &tilt_x, &tilt_y, &rotation, &flags],
+                ),
+                InputEvent::TouchBegin { slot, x, y } => {
+                    obj.emit_by_name::<()>("touch-begin", &[&slot, &x, &y])
+                }
+                InputEvent::TouchUpdate { slot, x, y } => {
+                    obj.emit_by_name::<()>("touch-update", &[&slot, &x, &y])
+                }
+                InputEvent::TouchEnd(slot) => obj.emit_by_name::<()>("touch-end", &[&slot]),
+                InputEvent::TouchCancel(slot) => obj.emit_by_name::<()>("touch-cancel", &[&slot]),
+            }
+        }
+
+        // pen events go to the stylus signal, if anyone listens
+        fn stylus_handled(&self, ec: &impl IsA<gtk::EventController>) -> bool {
+            let obj = self.obj();
+            let Some(signal) = glib::subclass::SignalId::lookup("stylus", obj.type_()) else {
+                return false;
+            };
+            ec.current_event().and_then(|e| e.device_tool()).is_some()
+                && glib::signal::signal_has_handler_pending(&*obj, signal, None, false)
+        }
+
+        // touch events are passed through if anyone listens, or emulate the mouse
+        fn touch_handled(&self) -> bool {
+            let obj = self.obj();
+            let Some(signal) = glib::subclass::SignalId::lookup("touch-begin", obj.type_()) else {
+                return false;
+            };
+            glib::signal::signal_has_handler_pending(&*obj, signal, None, false)
+        }
+
+        fn stylus_event(&self, gesture: &gtk::GestureStylus, x: f64, y: f64, contact: bool) {
+            let pos = {
+                let geometry = self.geometry();
+                self.model.borrow().transform_pos(&geometry, x, y)
+            };
+            let Some((x, y)) = pos else {
+                return;
+            };
+
+            let mut flags = StylusFlags::empty();
+            if contact {
+                flags |= StylusFlags::CONTACT;
+            }
+            if gesture
+                .device_tool()
+                .map_or(false, |t| t.tool_type() == gdk::DeviceToolType::Eraser)
+            {
+                flags |= StylusFlags::ERASER;
+            }
+            let state = gesture.current_event_state();
+            if state.contains(gdk::ModifierType::BUTTON2_MASK) {
+                flags |= StylusFlags::BARREL;
+            }
+            if state.contains(gdk::ModifierType::BUTTON3_MASK) {
+                flags |= StylusFlags::SECONDARY_BARREL;
+            }
+
+            let axis = |axis| gesture.axis(axis).unwrap_or(0.0);
+            self.emit_input(InputEvent::Stylus {
+                x,
+                y,
+                pressure: if contact {
+                    axis(gdk::AxisUse::Pressure)
+                } else {
+                    0.0
+                },
+                tilt_x: axis(gdk::AxisUse::Xtilt),
+                tilt_y: axis(gdk::AxisUse::Ytilt),
+                rotation: axis(gdk::AxisUse::Rotation),
+                flags,
+            });
+        }
+
+        // returns true if the event is handled
+        fn touch_event(&self, event: &gdk::Event) -> bool {
+            use gdk::EventType::*;
+
+            let type_ = event.event_type();
+            if !matches!(type_, TouchBegin | TouchUpdate | TouchEnd | TouchCancel) {
+                return false;
+            }
+            // only used as an identifier
+            let sequence = event.event_sequence().to_glib_none().0 as usize;
+            let Some((x, y)) = event
+                .position()
+                .and_then(|(x, y)| self.surface_to_widget(x, y))
+            else {
+                return false;
+            };
+
+            match self.touch_mode.get() {
+                TouchMode::PassThrough if self.touch_handled() => {
+                    self.touch_pass_through(type_, sequence, x, y);
+                    true
+                }
+                _ => {
+                    self.touch_emulate(type_, sequence, x, y);
+                    // let the zoom gesture see it
+                    false
+                }
+            }
+        }
+
+        fn surface_to_widget(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+            let obj = self.obj();
+            let native = obj.native()?;
+            let (tx, ty) = native.surface_transform();
+            native.translate_coordinates(&*obj, x - tx, y - ty)
+        }
+
+        fn touch_pass_through(&self, type_: gdk::EventType, sequence: usize, x: f64, y: f64) {
+            use gdk::EventType::*;
+
+            let slot = {
+                let mut slots = self.touch_slots.borrow_mut();
+                match slots.iter().position(|s| *s == Some(sequence)) {
+                    Some(slot) => slot,
+                    None if type_ == TouchBegin => match slots.iter().position(|s| s.is_none()) {
+                        Some(slot) => {
+                            slots[slot] = Some(sequence);
+                            slot
+                        }
+                        None => {
+                            slots.push(Some(sequence));
+                            slots.len() - 1
+                        }
+                    },
+                    None => return,
+                }
+            };
+            if matches!(type_, TouchEnd | TouchCancel) {
+                self.touch_slots.borrow_mut()[slot] = None;
+            }
+
+            let slot = slot as u32;
+            let pos = {
+                let geometry = self.geometry();
+                self.model.borrow().transform_pos(&geometry, x, y)
+            };
+            let event = match (type_, pos) {
+                (TouchBegin, Some((x, y))) => InputEvent::TouchBegin { slot, x, y },
+                (TouchUpdate, Some((x, y))) => InputEvent::TouchUpdate { slot, x, y },
+                (TouchEnd, _) => InputEvent::TouchEnd(slot),
+                (TouchCancel, _) => InputEvent::TouchCancel(slot),
+                _ => return,
+            };
+            self.emit_input(event);
+        }
+
+        fn touch_emulate(&self, type_: gdk::EventType, sequence: usize, x: f64, y: f64) {
+            use gdk::EventType::*;
+
+            if type_ == TouchBegin {
+                if self.touch_emulation.borrow().is_some() {
+                    // more fingers, probably a zoom gesture
+                    self.touch_emulation_cancel();
+                    return;
+                }
+                self.try_grab();
+                if let Some(event) = self.pointer_motion(x, y) {
+                    self.emit_input(event);
+                }
+                let long_press_id = glib::timeout_add_local_once(
+                    Duration::from_millis(LONG_PRESS_DELAY),
+                    clone!(@weak self as this => move || {
+                        this.touch_long_press();
+                    }),
+                );
+                self.touch_emulation.replace(Some(TouchEmulation {
+                    sequence,
+                    start: (x, y),
+                    moved: false,
+                    pressed: false,
+                    done: false,
+                    long_press_id: Some(long_press_id),
+                }));
+                return;
+            }
+
+            let Some(mut emulation) = self.touch_emulation.take() else {
+                return;
+            };
+            if emulation.sequence != sequence {
+                self.touch_emulation.replace(Some(emulation));
+                return;
+            }
+
+            match type_ {
+                TouchUpdate => {
+                    let (sx, sy) = emulation.start;
+                    if !emulation.moved && (x - sx).hypot(y - sy) > DRAG_THRESHOLD {
+                        emulation.moved = true;
+                        if let Some(id) = emulation.long_press_id.take() {
+                            id.remove();
+                        }
+                        if !emulation.done {
+                            emulation.pressed = true;
+                            self.emit_input(InputEvent::MousePress(MouseButton::Left));
+                        }
+                    }
+                    if emulation.moved {
+                        if let Some(event) = self.pointer_motion(x, y) {
+                            self.emit_input(event);
+                        }
+                    }
+                    self.touch_emulation.replace(Some(emulation));
+                }
+                TouchEnd => {
+                    if let Some(id) = emulation.long_press_id.take() {
+                        id.remove();
+                    }
+                    if emulation.pressed {
+                        self.emit_input(InputEvent::MouseRelease(MouseButton::Left));
+                    } else if !emulation.done {
+                        // a tap
+                        self.emit_input(InputEvent::MousePress(MouseButton::Left));
+                        self.emit_input(InputEvent::MouseRelease(MouseButton::Left));
+                    }
+                }
+                _ => {
+                    self.touch_emulation.replace(Some(emulation));
+                    self.touch_emulation_cancel();
+                }
+            }
+        }
+
+        fn touch_long_press(&self) {
+            let click = match self.touch_emulation.borrow_mut().as_mut() {
+                Some(emulation) => {
+                    emulation.long_press_id = None;
+                    emulation.done = !emulation.moved;
+                    emulation.done
+                }
+                None => false,
+            };
+            if !click {
+                return;
+            }
+            self.emit_input(InputEvent::MousePress(MouseButton::Right));
+            self.emit_input(InputEvent::MouseRelease(MouseButton::Right));
+        }
+
+        fn touch_emulation_cancel(&self) {
+            let Some(mut emulation) = self.touch_emulation.take() else {
+                return;
+            };
+            if let Some(id) = emulation.long_press_id.take() {
+                id.remove();
+            }
+            if emulation.pressed {
+                self.emit_input(InputEvent::MouseRelease(MouseButton::Left));
+            }
+        }
+
+        fn touch_cancel_all(&self) {
+            self.touch_emulation_cancel();
+            let slots = self.touch_slots.take();
+            for (slot, _) in slots.iter().enumerate().filter(|(_, s)| s.is_some()) {
+                self.emit_input(InputEvent::TouchCancel(slot as _));
+            }
+        }
+
+        fn transform_pos_unclamped(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+            let geometry = self.geometry();
+            self.model.borrow().transform_pos_unclamped(&geometry, x, y)
+        }
+
+        fn transform_pos_inv(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+            let geometry = self.geometry();
+            self.model.borrow().transform_pos_inv(&geometry, x, y)
+        }
+
+        fn toplevel(&self) -> Option<gdk::Toplevel> {
+            let obj = self.obj();
+            obj.root()
+                .and_then(|r| r.native())
+                .map(|n| n.surface())
+                .and_then(|s| s.downcast::<gdk::Toplevel>().ok())
+        }
+
+        // a surface can only have one fractional scale object
+        #[cfg(unix)]
+        fn create_fractional_scale(&self, handle: &wayland_client::QueueHandle<crate::Display>) {
+            let (Some(manager), Some(surface)) = (self.wl_fractional_manager.get(), self.surface())
+            else {
+                return;
+            };
+            let Some(wl_surface) = self.wl_surface() else {
+                return;
+            };
+            unsafe {
+                if surface.data::<bool>(FRACTIONAL_SCALE_KEY).is_some() {
+                    log::debug!("The surface already has a fractional scale");
+                    return;
+                }
+                surface.set_data(FRACTIONAL_SCALE_KEY, true);
+            }
+            let fractional = manager.get_fractional_scale(&wl_surface, handle, ());
+            self.wl_fractional_scale.replace(Some(fractional));
+        }
+
+        #[cfg(unix)]
+        fn destroy_fractional_scale(&self) {
+            if let Some(fractional) = self.wl_fractional_scale.take() {
+                fractional.destroy();
+                if let Some(surface) = self.surface() {
+                    unsafe {
+                        surface.steal_data::<bool>(FRACTIONAL_SCALE_KEY);
+                    }
+                }
+            }
+            if self.fractional_scale.take().is_some() {
+                self.queue_resize_request();
+            }
+        }
+
+        fn surface_scale_changed(&self, surface: &gdk::Surface) {
+            let scale = surface.property::<f64>("scale");
+            log::debug!("surface-scale: {}", scale);
+            if self.fractional_scale.replace(Some(scale)) != Some(scale) {
+                self.queue_resize_request();
+            }
+        }
+
+        // the effective scale of the widget, possibly fractional
+        pub(crate) fn scale(&self) -> f64 {
+            self.fractional_scale
+                .get()
+                .unwrap_or_else(|| self.obj().scale_factor() as f64)
+        }
+
+        pub(crate) fn queue_resize_request(&self) {
+            if let Some(timeout_id) = self.resize_timeout_id.take() {
+                timeout_id.remove();
+            }
+            let delay = match self.resize_policy.get() {
+                ResizePolicy::Immediate => 0,
+                ResizePolicy::OnIdle => self.resize_debounce.get(),
+                ResizePolicy::OnFullscreenOnly if self.is_fullscreen() => {
+                    self.resize_debounce.get()
+                }
+                _ => return,
+            };
+            // still deferred, as the handler may resize the widget
+            self.resize_timeout_id
+                .set(Some(glib::timeout_add_local_once(
+                    Duration::from_millis(delay as _),
+                    clone!(@weak self as this => move || {
+                        this.resize_timeout_id.set(None);
+                        this.resize_request();
+                    }),
+                )));
+        }
+
+        fn is_fullscreen(&self) -> bool {
+            self.obj()
+                .root()
+                .and_then(|r| r.downcast::<gtk::Window>().ok())
+                .map_or(false, |w| w.is_fullscreen())
+        }
+
+        fn resize_request(&self) {
+            let obj = self.obj();
+            let (width, height) = (obj.width(), obj.height());
+            let scale = self.scale();
+            let device = |size: i32| (size as f64 * scale).round() as u32;
+            let (width, height) = if self.is_rotated() {
+                (device(height), device(width))
+            } else {
+                (device(width), device(height))
+            };
+            self.emit_resize_request(width, height, false);
+        }
+
+        // width and height in remote device pixels
+        pub(crate) fn emit_resize_request(&self, width: u32, height: u32, force: bool) {
+            let snapped = self.model.borrow().snap_size(width, height);
+            let Some((width, height)) = snapped else {
+                log::debug!("The remote can't be resized");
+                return;
+            };
+            let scale = self.scale();
+            let logical = |size: u32| (size as f64 / scale).round() as i32;
+            let (w_mm, h_mm) = if self.is_rotated() {
+                let (h_mm, w_mm) = self
+                    .physical_size(logical(height), logical(width))
+                    .unwrap_or((0, 0));
+                (w_mm, h_mm)
+            } else {
+                self.physical_size(logical(width), logical(height))
+                    .unwrap_or((0, 0))
+            };
+
+            let request = (width, height, w_mm, h_mm, scale);
+            if force || Some(request) != self.last_resize_request.get() {
+                self.last_resize_request.set(Some(request));
+                self.obj()
+                    .emit_by_name::<()>("resize-request", &[&width, &height, &w_mm, &h_mm, &scale]);
+            }
+        }
+
+        pub(crate) fn set_supported_modes(&self, modes: &[(u32, u32)]) {
+            if self.model.borrow_mut().set_supported_modes(modes) {
+                self.last_resize_request.set(None);
+                self.queue_resize_request();
+            }
+        }
+
+        pub(crate) fn request_guest_resize(&self, width: u32, height: u32) {
+            if let Some(timeout_id) = self.resize_timeout_id.take() {
+                timeout_id.remove();
+            }
+            self.emit_resize_request(width, height, true);
+        }
+
+        // the widget size in mm, from the monitor it is on
+        fn physical_size(&self, width: i32, height: i32) -> Option<(u32, u32)> {
+            let surface = self.surface()?;
+            let monitor =
+                gdk::traits::DisplayExt::monitor_at_surface(&self.obj().display(), &surface);
+            let geom = monitor.geometry();
+            if geom.width() <= 0 || geom.height() <= 0 {
+                return None;
+            }
+            // both in logical pixels
+            Some((
+                (monitor.width_mm() as i64 * width as i64 / geom.width() as i64) as u32,
+                (monitor.height_mm() as i64 * height as i64 / geom.height() as i64) as u32,
+            ))
+        }
+
+        fn surface(&self) -> Option<gdk::Surface> {
+            let obj = self.obj();
+            obj.native().map(|n| n.surface())
+        }
+
+        #[cfg(windows)]
+        fn win32_handle(&self) -> Option<gdk_win32::HWND> {
+            self.surface()
+                .and_then(|s| s.downcast::<gdk_win32::Win32Surface>().ok())
+                .map(|w| w.handle())
+        }
+
+        #[cfg(unix)]
+        fn egl_surface(&self) -> Option<egl::Surface> {
+            self.egl_surf.get().copied()
+        }
+
+        #[cfg(unix)]
+        fn wl_surface(&self) -> Option<wayland_client::protocol::wl_surface::WlSurface> {
+            self.surface()
+                .and_then(|s| s.downcast::<gdk_wl::WaylandSurface>().ok())
+                .map(|w| w.wl_surface().unwrap())
+        }
+
+        #[cfg(unix)]
+        fn x11_xid(&self) -> Option<xlib::Window> {
+            self.surface()
+                .and_then(|s| s.downcast::<gdk_x11::X11Surface>().ok())
+                .map(|s| s.xid())
+        }
+
+        #[cfg(unix)]
+        pub(crate) fn egl_context(&self) -> Option<egl::Context> {
+            self.egl_display().and_then(|_| self.egl_ctx.get().copied())
+        }
+
+        // make the GL stream wait for the scanout producer
+        #[cfg(unix)]
+        pub(crate) fn wait_dmabuf_acquire(&self) {
+            let fd = match self.dmabuf.borrow_mut().as_mut() {
+                Some(d) if d.acquire_fd >= 0 => std::mem::replace(&mut d.acquire_fd, -1),
+                _ => return,
+            };
+
+            if let Some(egl_dpy) = self.egl_display() {
+                let egl = egl::egl();
+                let attribs = [
+                    egl::SYNC_NATIVE_FENCE_FD_ANDROID as usize,
+                    fd as usize,
+                    egl::NONE as usize,
+                ];
+                unsafe {
+                    match egl.create_sync(egl_dpy, egl::SYNC_NATIVE_FENCE_ANDROID, &attribs) {
+                        Ok(sync) => {
+                            // the fd is owned by the sync now
+                            if let Err(e) = egl.wait_sync(egl_dpy, sync, 0) {
+                                log::warn!("eglWaitSync() failed: {}", e);
+                            }
+                            if let Err(e) = egl.destroy_sync(egl_dpy, sync) {
+                                log::warn!("eglDestroySync() failed: {}", e);
+                            }
+                            return;
+                        }
+                        Err(e) => log::debug!("Failed to import acquire fence: {}", e),
+                    }
+                }
+            }
+
+            // fallback to a CPU wait
+            util::wait_fence(fd);
+        }
+
+        // a sync-fd signaled when the GL commands so far are done
+        #[cfg(unix)]
+        fn release_fence(&self) -> Option<std::os::unix::io::RawFd> {
+            let egl_dpy = self.egl_display()?;
+            let dup_fence_fd = egl::dup_native_fence_fd_android()?;
+            let egl = egl::egl();
+            unsafe {
+                let sync = egl
+                    .create_sync(
+                        egl_dpy,
+                        egl::SYNC_NATIVE_FENCE_ANDROID,
+                        &[egl::NONE as usize],
+                    )
+                    .map_err(|e| log::debug!("Failed to create release fence: {}", e))
+                    .ok()?;
+                // the fence fd is only available once flushed
+                gl::Flush();
+                let fd = dup_fence_fd(egl_dpy.as_ptr(), sync.as_ptr());
+                if let Err(e) = egl.destroy_sync(egl_dpy, sync) {
+                    log::warn!("eglDestroySync() failed: {}", e);
+                }
+                (fd >= 0).then(|| fd)
+            }
+        }
+
+        #[cfg(unix)]
+        pub(crate) fn emit_dmabuf_released(&self) {
+            self.obj().emit_by_name::<()>("dmabuf-released", &[]);
+        }
+
+        // emit dmabuf-released once the GPU is done reading the scanout, or now if there is none
+        #[cfg(unix)]
+        pub(crate) fn watch_dmabuf_release(&self) {
+            if self.dmabuf.borrow().is_none() {
+                self.emit_dmabuf_released();
+                return;
+            }
+
+            let this = self.obj().downgrade();
+            let released = move || {
+                if let Some(this) = this.upgrade() {
+                    this.emit_by_name::<()>("dmabuf-released", &[]);
+                }
+            };
+            match self.release_fence() {
+                Some(fd) => {
+                    glib::unix_fd_add_local(fd, glib::IOCondition::IN, move |fd, _| {
+                        unsafe {
+                            libc::close(fd);
+                        }
+                        released();
+                        glib::Continue(false)
+                    });
+                }
+                None => released(),
+            }
+        }
+
+        // bind the DMABUF planes (as a single image) to the texture
+        #[cfg(unix)]
+        pub(crate) fn import_dmabuf(
+            &self,
+            egl_dpy: egl::Display,
+            egl_image_target: egl::ImageTargetTexture2DOesFn,
+            (width, height, fourcc): (u32, u32, u32),
+            planes: &[crate::RdwDmabufPlane],
+            texture: GLuint,
+        ) -> bool {
+            let egl = egl::egl();
+            let mut attribs = vec![
+                egl::WIDTH as usize,
+                width as usize,
+                egl::HEIGHT as usize,
+                height as usize,
+                egl::LINUX_DRM_FOURCC_EXT as usize,
+                fourcc as usize,
+            ];
+            for (plane, attr) in planes.iter().zip(egl::DMA_BUF_PLANE_ATTRIBS) {
+                attribs.extend_from_slice(&[
+                    attr[0] as usize,
+                    plane.fd as usize,
+                    attr[1] as usize,
+                    plane.offset as usize,
+                    attr[2] as usize,
+                    plane.stride as usize,
+                ]);
+                if plane.modifier != egl::DRM_FORMAT_MOD_INVALID {
+                    attribs.extend_from_slice(&[
+                        attr[3] as usize,
+                        (plane.modifier & 0xffffffff) as usize,
+                        attr[4] as usize,
+                        (plane.modifier >> 32 & 0xffffffff) as usize,
+                    ]);
+                }
+            }
+            attribs.push(egl::NONE as usize);
+
+            let img = match egl.create_image(
+                egl_dpy,
+                egl::no_context(),
+                egl::LINUX_DMA_BUF_EXT,
+                egl::no_client_buffer(),
+                &attribs,
+            ) {
+                Ok(img) => img,
+                Err(e) => {
+                    log::warn!("eglCreateImage() failed: {}", e);
+                    return false;
+                }
+            };
+
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
+                egl_image_target(gl::TEXTURE_2D, img.as_ptr() as gl::types::GLeglImageOES);
+            }
+
+            if let Err(e) = egl.destroy_image(egl_dpy, img) {
+                log::warn!("eglDestroyImage() failed: {}", e);
+            }
+            true
+        }
+
+        #[cfg(unix)]
+        pub(crate) fn egl_display(&self) -> Option<egl::Display> {
+            let widget = self.obj();
+
+            if let Ok(dpy) = widget.display().downcast::<gdk_wl::WaylandDisplay>() {
+                return dpy.egl_display();
+            }
+
+            if let Ok(dpy) = widget.display().downcast::<gdk_x11::X11Display>() {
+                return dpy.egl_display();
+            };
+
+            None
+        }
+    }
+}
+
+impl Display {
+    pub fn make_cursor(
+        data: &[u8],
+        width: i32,
+        height: i32,
+        hot_x: i32,
+        hot_y: i32,
+        scale: i32,
+    ) -> gdk::Cursor {
+        assert!(scale > 0);
         let pb = gdk::gdk_pixbuf::Pixbuf::from_mut_slice(
             data.to_vec(),
             gdk::gdk_pixbuf::Colorspace::Rgb,
@@ -1276,6 +3949,86 @@ impl Display {
         let tex = gdk::Texture::for_pixbuf(&pb);
         gdk::Cursor::from_texture(&tex, hot_x * scale, hot_y * scale, None)
     }
+
+    /// Make a cursor from 1-bit AND and XOR masks, with rows padded to bytes.
+    ///
+    /// Pixels that invert the screen are drawn black with a white outline.
+    pub fn make_mono_cursor(
+        and_mask: &[u8],
+        xor_mask: &[u8],
+        width: i32,
+        height: i32,
+        hot_x: i32,
+        hot_y: i32,
+        scale: i32,
+    ) -> gdk::Cursor {
+        let data = mono_cursor_rgba(and_mask, xor_mask, width as _, height as _);
+        Self::make_cursor(&data, width, height, hot_x, hot_y, scale)
+    }
+}
+
+fn mono_cursor_rgba(and_mask: &[u8], xor_mask: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let stride = (width + 7) / 8;
+    let bit = |mask: &[u8], x: usize, y: usize| {
+        mask.get(y * stride + x / 8)
+            .map_or(false, |b| b & (0x80 >> (x % 8)) != 0)
+    };
+
+    let mut data = vec![0u8; width * height * 4];
+    let mut invert = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let px = &mut data[(y * width + x) * 4..][..4];
+            match (bit(and_mask, x, y), bit(xor_mask, x, y)) {
+                // transparent
+                (true, false) => {}
+                (false, xor) => {
+                    let v = if xor { 0xff } else { 0 };
+                    px.copy_from_slice(&[v, v, v, 0xff]);
+                }
+                (true, true) => {
+                    px.copy_from_slice(&[0, 0, 0, 0xff]);
+                    invert.push((x, y));
+                }
+            }
+        }
+    }
+    // outline the inverted pixels, to keep them visible on a dark background
+    for (x, y) in invert {
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                continue;
+            }
+            let px = &mut data[(ny as usize * width + nx as usize) * 4..][..4];
+            if px[3] == 0 {
+                px.copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+            }
+        }
+    }
+    data
+}
+
+// a small black dot with a white border, in RGBA
+#[cfg(not(feature = "bindings"))]
+fn dot_cursor_rgba() -> (Vec<u8>, i32) {
+    const SIZE: i32 = 7;
+    let c = (SIZE / 2) as f64;
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as _);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let d = ((x as f64 - c).powi(2) + (y as f64 - c).powi(2)).sqrt();
+            let px = if d <= 1.5 {
+                [0, 0, 0, 0xff]
+            } else if d <= 3.0 {
+                [0xff, 0xff, 0xff, 0xff]
+            } else {
+                [0, 0, 0, 0]
+            };
+            data.extend_from_slice(&px);
+        }
+    }
+    (data, SIZE)
 }
 
 #[cfg(not(feature = "bindings"))]
@@ -1328,8 +4081,49 @@ impl wayland_client::Dispatch<ZwpRelativePointerV1, ()> for Display {
         } = event
         {
             let scale = obj.scale_factor() as f64;
-            let (dx, dy) = (dx_unaccel / scale, dy_unaccel / scale);
-            obj.emit_by_name::<()>("motion-relative", &[&dx, &dy]);
+            let imp = obj.imp();
+            let event = imp
+                .model
+                .borrow()
+                .relative_motion(dx_unaccel / scale, dy_unaccel / scale);
+            imp.emit_input(event);
+        }
+    }
+}
+
+#[cfg(not(feature = "bindings"))]
+#[cfg(unix)]
+impl wayland_client::Dispatch<WpFractionalScaleManagerV1, ()> for Display {
+    fn event(
+        _state: &mut Self,
+        _: &WpFractionalScaleManagerV1,
+        event: wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_manager_v1::Event,
+        _: &(),
+        _: &wayland_client::Connection,
+        _: &wayland_client::QueueHandle<Self>,
+    ) {
+        log::trace!("{event:?}");
+    }
+}
+
+#[cfg(not(feature = "bindings"))]
+#[cfg(unix)]
+impl wayland_client::Dispatch<WpFractionalScaleV1, ()> for Display {
+    fn event(
+        obj: &mut Self,
+        _: &WpFractionalScaleV1,
+        event: wp_fractional_scale_v1::Event,
+        _: &(),
+        _: &wayland_client::Connection,
+        _: &wayland_client::QueueHandle<Self>,
+    ) {
+        if let wp_fractional_scale_v1::Event::PreferredScale { scale } = event {
+            // in 120ths
+            let scale = scale as f64 / 120.0;
+            log::debug!("preferred-scale: {}", scale);
+            let imp = obj.imp();
+            imp.fractional_scale.set(Some(scale));
+            imp.queue_resize_request();
         }
     }
 }
@@ -1364,21 +4158,85 @@ impl wayland_client::Dispatch<ZwpLockedPointerV1, ()> for Display {
     }
 }
 
-/// cbindgen:ignore
-pub const NONE_DISPLAY: Option<&Display> = None;
+/// cbindgen:ignore
+pub const NONE_DISPLAY: Option<&Display> = None;
+
+pub trait DisplayExt: 'static {
+    fn display_size(&self) -> Option<(usize, usize)>;
+
+    fn set_display_size(&self, size: Option<(usize, usize)>);
+
+    fn define_cursor(&self, cursor: Option<gdk::Cursor>);
+
+    fn mouse_absolute(&self) -> bool;
+
+    fn set_mouse_absolute(&self, absolute: bool);
+
+    fn scaling_mode(&self) -> ScalingMode;
+
+    fn set_scaling_mode(&self, mode: ScalingMode);
+
+    fn rotation(&self) -> Rotation;
+
+    fn set_rotation(&self, rotation: Rotation);
+
+    fn flip_horizontal(&self) -> bool;
+
+    fn set_flip_horizontal(&self, flip: bool);
+
+    fn flip_vertical(&self) -> bool;
+
+    fn set_flip_vertical(&self, flip: bool);
+
+    fn edge_pan(&self) -> bool;
+
+    fn set_edge_pan(&self, edge_pan: bool);
+
+    fn max_fps(&self) -> u32;
+
+    fn set_max_fps(&self, max_fps: u32);
+
+    fn force_software(&self) -> bool;
+
+    fn set_force_software(&self, force: bool);
+
+    fn software_rendering(&self) -> bool;
+
+    fn zoom_level(&self) -> f64;
+
+    fn set_zoom_level(&self, level: f64);
+
+    fn zoom_in(&self);
+
+    fn zoom_out(&self);
+
+    fn zoom_reset(&self);
+
+    fn set_cursor_position(&self, pos: Option<(usize, usize)>);
+
+    fn cursor_dot(&self) -> bool;
+
+    /// Press the keys in order, then release them in reverse, as `key-event` signals.
+    fn send_keys(&self, keyvals: &[gdk::Key]);
+
+    /// Type the text with the current keyboard layout, as `key-event` signals.
+    fn send_text(&self, text: &str);
 
-pub trait DisplayExt: 'static {
-    fn display_size(&self) -> Option<(usize, usize)>;
+    /// Type the text like `send_text`, one char every `typing-delay` ms, with a progress osd.
+    fn type_text(&self, text: &str);
 
-    fn set_display_size(&self, size: Option<(usize, usize)>);
+    /// Type the local clipboard text, with `type_text`.
+    fn type_clipboard(&self);
 
-    fn define_cursor(&self, cursor: Option<gdk::Cursor>);
+    fn cancel_typing(&self);
 
-    fn mouse_absolute(&self) -> bool;
+    fn typing(&self) -> bool;
 
-    fn set_mouse_absolute(&self, absolute: bool);
+    fn typing_delay(&self) -> u32;
 
-    fn set_cursor_position(&self, pos: Option<(usize, usize)>);
+    fn set_typing_delay(&self, delay: u32);
+
+    fn set_cursor_dot(&self, dot: bool);
 
     fn grab_shortcut(&self) -> gtk::ShortcutTrigger;
 
@@ -1386,11 +4244,58 @@ pub trait DisplayExt: 'static {
 
     fn update_area(&self, x: i32, y: i32, w: i32, h: i32, stride: i32, data: &[u8]);
 
+    #[allow(clippy::too_many_arguments)]
+    fn update_area_with_format(
+        &self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        stride: i32,
+        format: PixelFormat,
+        data: &[u8],
+    );
+
     #[cfg(unix)]
     fn set_dmabuf_scanout(&self, s: RdwDmabufScanout);
 
+    fn add_damage(&self, x: i32, y: i32, w: i32, h: i32);
+
     fn render(&self);
 
+    fn resize_policy(&self) -> ResizePolicy;
+
+    fn set_resize_policy(&self, policy: ResizePolicy);
+
+    fn resize_debounce(&self) -> u32;
+
+    fn set_resize_debounce(&self, debounce: u32);
+
+    fn resize_supported(&self) -> bool;
+
+    fn set_resize_supported(&self, supported: bool);
+
+    /// Set the sizes the remote supports, in device pixels. The resize requests are
+    /// snapped to one of them. An empty list allows any size.
+    fn set_supported_modes(&self, modes: &[(u32, u32)]);
+
+    /// Ask the remote to resize to the given size, in device pixels, regardless of the
+    /// resize policy. Use `ResizePolicy::None` to keep the widget size from overriding it.
+    fn request_guest_resize(&self, width: u32, height: u32);
+
+    fn snapshot_texture(&self) -> Option<gdk::Texture>;
+
+    fn save_png_future(
+        &self,
+        file: &gio::File,
+    ) -> Pin<Box<dyn Future<Output = Result<(), glib::Error>> + 'static>>;
+
+    fn save_png_async<P: FnOnce(Result<(), glib::Error>) + 'static>(
+        &self,
+        file: &gio::File,
+        callback: P,
+    );
+
     fn set_alternative_text(&self, alt_text: &str);
 
     fn connect_key_event<F: Fn(&Self, u32, u32, KeyEvent) + 'static>(
@@ -1402,18 +4307,60 @@ pub trait DisplayExt: 'static {
 
     fn connect_motion_relative<F: Fn(&Self, f64, f64) + 'static>(&self, f: F) -> SignalHandlerId;
 
-    fn connect_mouse_press<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId;
+    fn connect_mouse_press<F: Fn(&Self, MouseButton) + 'static>(&self, f: F) -> SignalHandlerId;
 
-    fn connect_mouse_release<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId;
+    fn connect_mouse_release<F: Fn(&Self, MouseButton) + 'static>(&self, f: F) -> SignalHandlerId;
 
+    /// Scroll steps, accumulated by GTK from the smooth deltas.
+    ///
+    /// Both scroll signals are emitted for each scroll event: a backend must only
+    /// connect one of `scroll-discrete` and `scroll-smooth`.
     fn connect_scroll_discrete<F: Fn(&Self, Scroll) + 'static>(&self, f: F) -> SignalHandlerId;
 
+    /// Scroll deltas in wheel clicks, and in 120ths of a click (as Windows and libinput v120).
+    ///
+    /// Touchpads scroll in surface units instead, and the 120ths are then 0. A backend
+    /// must only connect one of `scroll-discrete` and `scroll-smooth`.
+    fn connect_scroll_smooth<F: Fn(&Self, f64, f64, i32, i32) + 'static>(
+        &self,
+        f: F,
+    ) -> SignalHandlerId;
+
+    /// Pen input: position, pressure (0 to 1), tilt and rotation as reported by GDK.
+    ///
+    /// When connected, pen events are no longer sent as mouse events.
+    #[allow(clippy::type_complexity)]
+    fn connect_stylus<F: Fn(&Self, f64, f64, f64, f64, f64, f64, StylusFlags) + 'static>(
+        &self,
+        f: F,
+    ) -> SignalHandlerId;
+
+    fn connect_touch_begin<F: Fn(&Self, u32, f64, f64) + 'static>(&self, f: F) -> SignalHandlerId;
+
+    fn connect_touch_update<F: Fn(&Self, u32, f64, f64) + 'static>(&self, f: F) -> SignalHandlerId;
+
+    fn connect_touch_end<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId;
+
+    fn connect_touch_cancel<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId;
+
+    fn touch_mode(&self) -> TouchMode;
+
+    fn set_touch_mode(&self, mode: TouchMode);
+
     fn connect_property_grabbed_notify<F: Fn(&Self) + 'static>(&self, f: F) -> SignalHandlerId;
 
-    fn connect_resize_request<F: Fn(&Self, u32, u32, u32, u32) + 'static>(
+    fn connect_resize_request<F: Fn(&Self, u32, u32, u32, u32, f64) + 'static>(
         &self,
         f: F,
     ) -> SignalHandlerId;
+
+    fn connect_frame_damage<F: Fn(&Self, &cairo::Region) + 'static>(&self, f: F)
+        -> SignalHandlerId;
+
+    /// Emitted when the scanout of the last render() may be reused, right away
+    /// if it isn't read (software rendering, failed import, unrealized widget).
+    #[cfg(unix)]
+    fn connect_dmabuf_released<F: Fn(&Self) + 'static>(&self, f: F) -> SignalHandlerId;
 }
 
 impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O {
@@ -1438,7 +4385,7 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         {
             let imp = imp::Display::from_obj(self_);
 
-            imp.display_size.get()
+            imp.display_size()
         }
     }
 
@@ -1459,35 +4406,7 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         {
             let imp = imp::Display::from_obj(self_);
 
-            if self.display_size() == size {
-                return;
-            }
-
-            imp.display_size.replace(size);
-
-            if !self.is_realized() {
-                return;
-            }
-
-            let _ctx = imp.make_current();
-            if let Some((width, height)) = size {
-                unsafe {
-                    gl::BindTexture(gl::TEXTURE_2D, imp.texture_id());
-                    gl::TexImage2D(
-                        gl::TEXTURE_2D,
-                        0,
-                        gl::RGB as _,
-                        width as _,
-                        height as _,
-                        0,
-                        gl::BGRA,
-                        gl::UNSIGNED_BYTE,
-                        std::ptr::null(),
-                    );
-                }
-            }
-
-            self.queue_resize();
+            imp.set_display_size(size);
         }
     }
 
@@ -1502,10 +4421,13 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         #[cfg(not(feature = "bindings"))]
         {
             let imp = imp::Display::from_obj(self_);
+            let image = cursor.as_ref().and_then(imp::cursor_image);
+            imp.model.borrow_mut().set_cursor(image);
+            imp.cursor.replace(cursor);
             if self.mouse_absolute() {
-                imp.gl_area().set_cursor(cursor.as_ref());
+                imp.set_widget_cursor(imp.shown_cursor().as_ref());
             }
-            imp.cursor.replace(cursor);
+            self.queue_draw(); // update cursor
         }
     }
 
@@ -1517,6 +4439,145 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         glib::ObjectExt::set_property(self, "mouse-absolute", absolute);
     }
 
+    fn scaling_mode(&self) -> ScalingMode {
+        self.property("scaling-mode")
+    }
+
+    fn set_scaling_mode(&self, mode: ScalingMode) {
+        glib::ObjectExt::set_property(self, "scaling-mode", mode);
+    }
+
+    fn rotation(&self) -> Rotation {
+        self.property("rotation")
+    }
+
+    fn set_rotation(&self, rotation: Rotation) {
+        glib::ObjectExt::set_property(self, "rotation", rotation);
+    }
+
+    fn flip_horizontal(&self) -> bool {
+        self.property("flip-horizontal")
+    }
+
+    fn set_flip_horizontal(&self, flip: bool) {
+        glib::ObjectExt::set_property(self, "flip-horizontal", flip);
+    }
+
+    fn flip_vertical(&self) -> bool {
+        self.property("flip-vertical")
+    }
+
+    fn set_flip_vertical(&self, flip: bool) {
+        glib::ObjectExt::set_property(self, "flip-vertical", flip);
+    }
+
+    fn edge_pan(&self) -> bool {
+        self.property("edge-pan")
+    }
+
+    fn set_edge_pan(&self, edge_pan: bool) {
+        glib::ObjectExt::set_property(self, "edge-pan", edge_pan);
+    }
+
+    fn max_fps(&self) -> u32 {
+        self.property("max-fps")
+    }
+
+    fn set_max_fps(&self, max_fps: u32) {
+        glib::ObjectExt::set_property(self, "max-fps", max_fps);
+    }
+
+    fn force_software(&self) -> bool {
+        self.property("force-software")
+    }
+
+    fn set_force_software(&self, force: bool) {
+        glib::ObjectExt::set_property(self, "force-software", force);
+    }
+
+    fn software_rendering(&self) -> bool {
+        self.property("software-rendering")
+    }
+
+    fn zoom_level(&self) -> f64 {
+        self.property("zoom-level")
+    }
+
+    fn set_zoom_level(&self, level: f64) {
+        glib::ObjectExt::set_property(self, "zoom-level", level);
+    }
+
+    fn resize_policy(&self) -> ResizePolicy {
+        self.property("resize-policy")
+    }
+
+    fn set_resize_policy(&self, policy: ResizePolicy) {
+        glib::ObjectExt::set_property(self, "resize-policy", policy);
+    }
+
+    fn resize_debounce(&self) -> u32 {
+        self.property("resize-debounce")
+    }
+
+    fn set_resize_debounce(&self, debounce: u32) {
+        glib::ObjectExt::set_property(self, "resize-debounce", debounce);
+    }
+
+    fn resize_supported(&self) -> bool {
+        self.property("resize-supported")
+    }
+
+    fn set_resize_supported(&self, supported: bool) {
+        glib::ObjectExt::set_property(self, "resize-supported", supported);
+    }
+
+    fn set_supported_modes(&self, modes: &[(u32, u32)]) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            let modes: Vec<u32> = modes.iter().flat_map(|(w, h)| [*w, *h]).collect();
+            ffi::rdw_display_set_supported_modes(
+                self_.to_glib_none().0,
+                modes.as_ptr(),
+                modes.len() / 2,
+            );
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.set_supported_modes(modes);
+        }
+    }
+
+    fn request_guest_resize(&self, width: u32, height: u32) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            ffi::rdw_display_request_guest_resize(self_.to_glib_none().0, width, height);
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.request_guest_resize(width, height);
+        }
+    }
+
+    fn zoom_in(&self) {
+        self.set_zoom_level((self.zoom_level() * ZOOM_STEP).min(ZOOM_MAX));
+    }
+
+    fn zoom_out(&self) {
+        self.set_zoom_level((self.zoom_level() / ZOOM_STEP).max(ZOOM_MIN));
+    }
+
+    fn zoom_reset(&self) {
+        self.set_zoom_level(1.0);
+    }
+
     fn set_cursor_position(&self, pos: Option<(usize, usize)>) {
         // Safety: safe because IsA<Display>
         let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
@@ -1533,11 +4594,108 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         {
             let imp = imp::Display::from_obj(self_);
 
-            imp.cursor_position.set(pos);
+            imp.model.borrow_mut().set_cursor_position(pos);
+            imp.update_edge_pan();
             self.queue_draw();
         }
     }
 
+    fn cursor_dot(&self) -> bool {
+        self.property("cursor-dot")
+    }
+
+    fn send_keys(&self, keyvals: &[gdk::Key]) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            let keyvals: Vec<u32> = keyvals.iter().map(|k| k.into_glib()).collect();
+            ffi::rdw_display_send_keys(self_.to_glib_none().0, keyvals.as_ptr(), keyvals.len());
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.send_keys(keyvals);
+        }
+    }
+
+    fn send_text(&self, text: &str) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            ffi::rdw_display_send_text(self_.to_glib_none().0, text.to_glib_none().0);
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.send_text(text);
+        }
+    }
+
+    fn type_text(&self, text: &str) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            ffi::rdw_display_type_text(self_.to_glib_none().0, text.to_glib_none().0);
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.type_text(text);
+        }
+    }
+
+    fn type_clipboard(&self) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            ffi::rdw_display_type_clipboard(self_.to_glib_none().0);
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.type_clipboard();
+        }
+    }
+
+    fn cancel_typing(&self) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            ffi::rdw_display_cancel_typing(self_.to_glib_none().0);
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.cancel_typing();
+        }
+    }
+
+    fn typing(&self) -> bool {
+        self.property("typing")
+    }
+
+    fn typing_delay(&self) -> u32 {
+        self.property("typing-delay")
+    }
+
+    fn set_typing_delay(&self, delay: u32) {
+        glib::ObjectExt::set_property(self, "typing-delay", delay);
+    }
+
+    fn set_cursor_dot(&self, dot: bool) {
+        glib::ObjectExt::set_property(self, "cursor-dot", dot);
+    }
+
     fn grab_shortcut(&self) -> gtk::ShortcutTrigger {
         self.property("grab-shortcut")
     }
@@ -1547,40 +4705,39 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
     }
 
     fn update_area(&self, x: i32, y: i32, w: i32, h: i32, stride: i32, data: &[u8]) {
+        self.update_area_with_format(x, y, w, h, stride, PixelFormat::Xrgb8888, data);
+    }
+
+    fn update_area_with_format(
+        &self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        stride: i32,
+        format: PixelFormat,
+        data: &[u8],
+    ) {
         // Safety: safe because IsA<Display>
         let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
 
         #[cfg(feature = "bindings")]
         unsafe {
-            ffi::rdw_display_update_area(self_.to_glib_none().0, x, y, w, h, stride, data.as_ptr());
+            ffi::rdw_display_update_area_with_format(
+                self_.to_glib_none().0,
+                x,
+                y,
+                w,
+                h,
+                stride,
+                format.into_glib(),
+                data.as_ptr(),
+            );
         }
         #[cfg(not(feature = "bindings"))]
         {
             let imp = imp::Display::from_obj(self_);
-            let _ctx = imp.make_current();
-
-            // TODO: check data boundaries
-            unsafe {
-                gl::BindTexture(gl::TEXTURE_2D, imp.texture_id());
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
-                gl::PixelStorei(gl::UNPACK_ROW_LENGTH, stride / 4);
-                gl::TexSubImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    x,
-                    y,
-                    w,
-                    h,
-                    gl::BGRA,
-                    gl::UNSIGNED_BYTE,
-                    data.as_ptr() as _,
-                );
-            }
-
-            #[cfg(unix)]
-            imp.dmabuf.replace(None);
-            imp.gl_area().queue_render();
+            imp.update_area(x, y, w, h, stride, format, data);
         }
     }
 
@@ -1596,9 +4753,12 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         #[cfg(all(unix, not(feature = "bindings")))]
         {
             let imp = imp::Display::from_obj(self_);
+            if imp.software.get() {
+                imp.download_dmabuf(s);
+                return;
+            }
             let _ctx = imp.make_current();
 
-            let egl = egl::egl();
             let egl_image_target = match egl::image_target_texture_2d_oes() {
                 Some(func) => func,
                 _ => {
@@ -1615,51 +4775,60 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
                 }
             };
 
-            let attribs = vec![
-                egl::WIDTH as usize,
-                s.width as usize,
-                egl::HEIGHT as usize,
-                s.height as usize,
-                egl::LINUX_DRM_FOURCC_EXT as usize,
-                s.fourcc as usize,
-                egl::DMA_BUF_PLANE0_FD_EXT as usize,
-                s.fd as usize,
-                egl::DMA_BUF_PLANE0_PITCH_EXT as usize,
-                s.stride as usize,
-                egl::DMA_BUF_PLANE0_OFFSET_EXT as usize,
-                0,
-                egl::DMA_BUF_PLANE0_MODIFIER_LO_EXT as usize,
-                (s.modifier & 0xffffffff) as usize,
-                egl::DMA_BUF_PLANE0_MODIFIER_HI_EXT as usize,
-                (s.modifier >> 32 & 0xffffffff) as usize,
-                egl::NONE as usize,
-            ];
-
-            let img = match egl.create_image(
-                egl_dpy,
-                egl::no_context(),
-                egl::LINUX_DMA_BUF_EXT,
-                egl::no_client_buffer(),
-                &attribs,
-            ) {
-                Ok(img) => img,
-                Err(e) => {
-                    log::warn!("eglCreateImage() failed: {}", e);
-                    return;
+            let planes = s.planes();
+            let yuv = match s.fourcc {
+                egl::DRM_FORMAT_NV12 => Some((egl::DRM_FORMAT_R8, egl::DRM_FORMAT_GR88)),
+                egl::DRM_FORMAT_P010 => Some((egl::DRM_FORMAT_R16, egl::DRM_FORMAT_GR1616)),
+                _ => None,
+            };
+            let imported = match yuv {
+                // import the planes separately, texture-blit.frag does the conversion
+                Some((y_fourcc, uv_fourcc)) if planes.len() >= 2 => {
+                    imp.import_dmabuf(
+                        egl_dpy,
+                        egl_image_target,
+                        (s.width, s.height, y_fourcc),
+                        &planes[..1],
+                        imp.texture_id(),
+                    ) && imp.import_dmabuf(
+                        egl_dpy,
+                        egl_image_target,
+                        ((s.width + 1) / 2, (s.height + 1) / 2, uv_fourcc),
+                        &planes[1..2],
+                        imp.texture_uv_id.get(),
+                    )
                 }
+                _ => imp.import_dmabuf(
+                    egl_dpy,
+                    egl_image_target,
+                    (s.width, s.height, s.fourcc),
+                    planes,
+                    imp.texture_id(),
+                ),
             };
-
-            unsafe {
-                gl::BindTexture(gl::TEXTURE_2D, imp.texture_id());
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as _);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
-                egl_image_target(gl::TEXTURE_2D, img.as_ptr() as gl::types::GLeglImageOES);
+            if !imported {
+                return;
             }
 
+            imp.add_damage(0, 0, s.width as _, s.height as _);
+            imp.pending_uploads.borrow_mut().clear();
             imp.dmabuf.replace(Some(s));
+        }
+    }
 
-            if let Err(e) = egl.destroy_image(egl_dpy, img) {
-                log::warn!("eglDestroyImage() failed: {}", e);
+    fn add_damage(&self, x: i32, y: i32, w: i32, h: i32) {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            ffi::rdw_display_add_damage(self_.to_glib_none().0, x, y, w, h);
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            if imp.add_damage(x, y, w, h) {
+                imp.queue_render();
             }
         }
     }
@@ -1675,27 +4844,128 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         #[cfg(not(feature = "bindings"))]
         {
             let imp = imp::Display::from_obj(self_);
+            if imp.software.get() || !imp.gl_realized() {
+                // damage is reported by snapshot()
+                self.queue_draw();
+                // nothing reads the scanout, the producer must not wait
+                #[cfg(unix)]
+                imp.emit_dmabuf_released();
+                return;
+            }
             let _ctx = imp.make_current();
 
+            #[cfg(not(unix))]
+            let flip = false;
+            #[cfg(unix)]
+            let flip = imp.dmabuf.borrow().as_ref().map_or(false, |d| d.y0_top);
+            let damage = imp.take_damage();
+            let unchanged = imp.frame_unchanged(&imp.frame_state(flip));
+            if unchanged && damage.is_none() {
+                // the target already holds this frame
+                #[cfg(unix)]
+                imp.emit_dmabuf_released();
+                return;
+            }
+
+            // GL viewport origin is bottom-left
+            let height = self.height() * self.scale_factor();
             unsafe {
                 gl::ClearColor(0.1, 0.1, 0.1, 1.0);
-                gl::Clear(gl::COLOR_BUFFER_BIT);
                 gl::Disable(gl::BLEND);
 
                 if let Some(vp) = imp.viewport() {
-                    gl::Viewport(vp.x(), vp.y(), vp.width(), vp.height());
-                    #[cfg(not(unix))]
-                    let flip = false;
+                    gl::Viewport(
+                        vp.x(),
+                        height - vp.y() - vp.height(),
+                        vp.width(),
+                        vp.height(),
+                    );
                     #[cfg(unix)]
-                    let flip = imp.dmabuf.borrow().as_ref().map_or(false, |d| d.y0_top);
-                    imp.texture_blit(flip);
+                    imp.wait_dmabuf_acquire();
+                    match damage.as_ref().filter(|_| unchanged) {
+                        Some(damage) => {
+                            // only redraw what changed since the last frame
+                            gl::Enable(gl::SCISSOR_TEST);
+                            for (x, y, w, h) in imp.damage_scissors(damage, &vp) {
+                                gl::Scissor(x, y, w, h);
+                                gl::Clear(gl::COLOR_BUFFER_BIT);
+                                imp.texture_blit(flip);
+                            }
+                            gl::Disable(gl::SCISSOR_TEST);
+                        }
+                        None => {
+                            gl::Clear(gl::COLOR_BUFFER_BIT);
+                            imp.texture_blit(flip);
+                        }
+                    }
+                } else {
+                    gl::Clear(gl::COLOR_BUFFER_BIT);
                 }
             }
 
+            #[cfg(unix)]
+            imp.watch_dmabuf_release();
+
             imp.gl_area().queue_draw();
+
+            if let Some(damage) = damage {
+                self.emit_by_name::<()>("frame-damage", &[&damage]);
+            }
+        }
+    }
+
+    fn snapshot_texture(&self) -> Option<gdk::Texture> {
+        // Safety: safe because IsA<Display>
+        let self_: &Display = unsafe { self.unsafe_cast_ref::<Display>() };
+
+        #[cfg(feature = "bindings")]
+        unsafe {
+            from_glib_full(ffi::rdw_display_snapshot_texture(self_.to_glib_none().0))
+        }
+        #[cfg(not(feature = "bindings"))]
+        {
+            let imp = imp::Display::from_obj(self_);
+            imp.snapshot_texture()
         }
     }
 
+    fn save_png_future(
+        &self,
+        file: &gio::File,
+    ) -> Pin<Box<dyn Future<Output = Result<(), glib::Error>> + 'static>> {
+        let texture = self.snapshot_texture();
+        let file = file.clone();
+        Box::pin(async move {
+            let texture = texture
+                .ok_or_else(|| glib::Error::new(crate::Error::Failed, "No display content"))?;
+            let pixbuf = gdk::pixbuf_get_from_texture(&texture).ok_or_else(|| {
+                glib::Error::new(crate::Error::Failed, "Failed to convert the texture")
+            })?;
+            let stream = file
+                .replace_future(
+                    None,
+                    false,
+                    gio::FileCreateFlags::REPLACE_DESTINATION,
+                    glib::PRIORITY_DEFAULT,
+                )
+                .await?;
+            pixbuf.save_to_streamv_future(&stream, "png", &[]).await?;
+            stream.close_future(glib::PRIORITY_DEFAULT).await?;
+            Ok(())
+        })
+    }
+
+    fn save_png_async<P: FnOnce(Result<(), glib::Error>) + 'static>(
+        &self,
+        file: &gio::File,
+        callback: P,
+    ) {
+        let future = self.save_png_future(file);
+        glib::MainContext::default().spawn_local(async move {
+            callback(future.await);
+        });
+    }
+
     fn set_alternative_text(&self, alt_text: &str) {
         self.update_property(&[gtk::accessible::Property::Description(alt_text)]);
     }
@@ -1782,10 +5052,10 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         }
     }
 
-    fn connect_mouse_press<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId {
-        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32) + 'static>(
+    fn connect_mouse_press<F: Fn(&Self, MouseButton) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, MouseButton) + 'static>(
             this: *mut RdwDisplay,
-            button: u32,
+            button: crate::ffi::RdwMouseButton,
             f: glib::ffi::gpointer,
         ) where
             P: IsA<Display>,
@@ -1793,7 +5063,7 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
             let f = &*(f as *const F);
             f(
                 Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
-                button,
+                from_glib(button),
             )
         }
         unsafe {
@@ -1807,10 +5077,10 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         }
     }
 
-    fn connect_mouse_release<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId {
-        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32) + 'static>(
+    fn connect_mouse_release<F: Fn(&Self, MouseButton) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, MouseButton) + 'static>(
             this: *mut RdwDisplay,
-            button: u32,
+            button: crate::ffi::RdwMouseButton,
             f: glib::ffi::gpointer,
         ) where
             P: IsA<Display>,
@@ -1818,7 +5088,7 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
             let f = &*(f as *const F);
             f(
                 Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
-                button,
+                from_glib(button),
             )
         }
         unsafe {
@@ -1857,6 +5127,194 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
         }
     }
 
+    fn connect_scroll_smooth<F: Fn(&Self, f64, f64, i32, i32) + 'static>(
+        &self,
+        f: F,
+    ) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, f64, f64, i32, i32) + 'static>(
+            this: *mut RdwDisplay,
+            dx: f64,
+            dy: f64,
+            v120_x: i32,
+            v120_y: i32,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(
+                Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
+                dx,
+                dy,
+                v120_x,
+                v120_y,
+            )
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"scroll-smooth\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    fn connect_stylus<F: Fn(&Self, f64, f64, f64, f64, f64, f64, StylusFlags) + 'static>(
+        &self,
+        f: F,
+    ) -> SignalHandlerId {
+        #[allow(clippy::too_many_arguments)]
+        unsafe extern "C" fn connect_trampoline<
+            P,
+            F: Fn(&P, f64, f64, f64, f64, f64, f64, StylusFlags) + 'static,
+        >(
+            this: *mut RdwDisplay,
+            x: f64,
+            y: f64,
+            pressure: f64,
+            tilt_x: f64,
+            tilt_y: f64,
+            rotation: f64,
+            flags: StylusFlags,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(
+                Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
+                x,
+                y,
+                pressure,
+                tilt_x,
+                tilt_y,
+                rotation,
+                flags,
+            )
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"stylus\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    fn connect_touch_begin<F: Fn(&Self, u32, f64, f64) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32, f64, f64) + 'static>(
+            this: *mut RdwDisplay,
+            slot: u32,
+            x: f64,
+            y: f64,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(
+                Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
+                slot,
+                x,
+                y,
+            )
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"touch-begin\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    fn connect_touch_update<F: Fn(&Self, u32, f64, f64) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32, f64, f64) + 'static>(
+            this: *mut RdwDisplay,
+            slot: u32,
+            x: f64,
+            y: f64,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(
+                Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
+                slot,
+                x,
+                y,
+            )
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"touch-update\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    fn connect_touch_end<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32) + 'static>(
+            this: *mut RdwDisplay,
+            slot: u32,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(Display::from_glib_borrow(this).unsafe_cast_ref::<P>(), slot)
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"touch-end\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    fn connect_touch_cancel<F: Fn(&Self, u32) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32) + 'static>(
+            this: *mut RdwDisplay,
+            slot: u32,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(Display::from_glib_borrow(this).unsafe_cast_ref::<P>(), slot)
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"touch-cancel\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    fn touch_mode(&self) -> TouchMode {
+        self.property("touch-mode")
+    }
+
+    fn set_touch_mode(&self, mode: TouchMode) {
+        glib::ObjectExt::set_property(self, "touch-mode", mode);
+    }
+
     fn connect_property_grabbed_notify<F: Fn(&Self) + 'static>(&self, f: F) -> SignalHandlerId {
         unsafe extern "C" fn notify_trampoline<P, F: Fn(&P) + 'static>(
             this: *mut RdwDisplay,
@@ -1880,16 +5338,17 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
             )
         }
     }
-    fn connect_resize_request<F: Fn(&Self, u32, u32, u32, u32) + 'static>(
+    fn connect_resize_request<F: Fn(&Self, u32, u32, u32, u32, f64) + 'static>(
         &self,
         f: F,
     ) -> SignalHandlerId {
-        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32, u32, u32, u32) + 'static>(
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, u32, u32, u32, u32, f64) + 'static>(
             this: *mut RdwDisplay,
             width: u32,
             height: u32,
             width_mm: u32,
             height_mm: u32,
+            scale: f64,
             f: glib::ffi::gpointer,
         ) where
             P: IsA<Display>,
@@ -1901,6 +5360,7 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
                 height,
                 width_mm,
                 height_mm,
+                scale,
             )
         }
         unsafe {
@@ -1913,6 +5373,56 @@ impl<O: IsA<Display> + IsA<gtk::Widget> + IsA<gtk::Accessible>> DisplayExt for O
             )
         }
     }
+
+    fn connect_frame_damage<F: Fn(&Self, &cairo::Region) + 'static>(
+        &self,
+        f: F,
+    ) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P, &cairo::Region) + 'static>(
+            this: *mut RdwDisplay,
+            region: *mut cairo::ffi::cairo_region_t,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(
+                Display::from_glib_borrow(this).unsafe_cast_ref::<P>(),
+                &from_glib_borrow(region),
+            )
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"frame-damage\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
+
+    #[cfg(unix)]
+    fn connect_dmabuf_released<F: Fn(&Self) + 'static>(&self, f: F) -> SignalHandlerId {
+        unsafe extern "C" fn connect_trampoline<P, F: Fn(&P) + 'static>(
+            this: *mut RdwDisplay,
+            f: glib::ffi::gpointer,
+        ) where
+            P: IsA<Display>,
+        {
+            let f = &*(f as *const F);
+            f(Display::from_glib_borrow(this).unsafe_cast_ref::<P>())
+        }
+        unsafe {
+            let f: Box<F> = Box::new(f);
+            glib::signal::connect_raw(
+                self.as_ptr() as *mut glib::gobject_ffi::GObject,
+                b"dmabuf-released\0".as_ptr() as *const _,
+                Some(std::mem::transmute(connect_trampoline::<Self, F> as usize)),
+                Box::into_raw(f),
+            )
+        }
+    }
 }
 
 pub trait DisplayImpl: DisplayImplExt + WidgetImpl {}
@@ -1933,7 +5443,7 @@ unsafe impl<T: DisplayImpl> IsSubclassable<T> for Display {
 
 #[cfg(not(feature = "bindings"))]
 glib::wrapper! {
-    pub struct Display(ObjectSubclass<imp::Display>) @extends gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+    pub struct Display(ObjectSubclass<imp::Display>) @extends gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
 }
 
 /// cbindgen:ignore
@@ -1971,8 +5481,42 @@ mod ffi {
             data: *const u8,
         );
 
+        pub fn rdw_display_add_damage(dpy: *mut RdwDisplay, x: i32, y: i32, w: i32, h: i32);
+
+        #[allow(clippy::too_many_arguments)]
+        pub fn rdw_display_update_area_with_format(
+            dpy: *mut RdwDisplay,
+            x: i32,
+            y: i32,
+            w: i32,
+            h: i32,
+            stride: i32,
+            format: crate::ffi::RdwPixelFormat,
+            data: *const u8,
+        );
+
         pub fn rdw_display_render(dpy: *mut RdwDisplay);
 
+        pub fn rdw_display_snapshot_texture(dpy: *mut RdwDisplay) -> *mut gdk::ffi::GdkTexture;
+
+        pub fn rdw_display_send_keys(dpy: *mut RdwDisplay, keyvals: *const u32, n_keyvals: usize);
+
+        pub fn rdw_display_send_text(dpy: *mut RdwDisplay, text: *const std::os::raw::c_char);
+
+        pub fn rdw_display_type_text(dpy: *mut RdwDisplay, text: *const std::os::raw::c_char);
+
+        pub fn rdw_display_type_clipboard(dpy: *mut RdwDisplay);
+
+        pub fn rdw_display_cancel_typing(dpy: *mut RdwDisplay);
+
+        pub fn rdw_display_set_supported_modes(
+            dpy: *mut RdwDisplay,
+            modes: *const u32,
+            n_modes: usize,
+        );
+
+        pub fn rdw_display_request_guest_resize(dpy: *mut RdwDisplay, width: u32, height: u32);
+
         #[cfg(unix)]
         pub fn rdw_display_set_dmabuf_scanout(
             dpy: *mut RdwDisplay,
@@ -1983,7 +5527,7 @@ mod ffi {
 
 #[cfg(feature = "bindings")]
 glib::wrapper! {
-    pub struct Display(Object<RdwDisplay, RdwDisplayClass>) @extends gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+    pub struct Display(Object<RdwDisplay, RdwDisplayClass>) @extends gtk::Widget, @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
 
     match fn {
         type_ => || ffi::rdw_display_get_type(),
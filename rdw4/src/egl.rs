@@ -26,6 +26,70 @@ mod imp {
     pub(crate) const DMA_BUF_PLANE0_PITCH_EXT: Int = 0x3274;
     pub(crate) const DMA_BUF_PLANE0_MODIFIER_LO_EXT: Int = 0x3443;
     pub(crate) const DMA_BUF_PLANE0_MODIFIER_HI_EXT: Int = 0x3444;
+    pub(crate) const DMA_BUF_PLANE1_FD_EXT: Int = 0x3275;
+    pub(crate) const DMA_BUF_PLANE1_OFFSET_EXT: Int = 0x3276;
+    pub(crate) const DMA_BUF_PLANE1_PITCH_EXT: Int = 0x3277;
+    pub(crate) const DMA_BUF_PLANE1_MODIFIER_LO_EXT: Int = 0x3445;
+    pub(crate) const DMA_BUF_PLANE1_MODIFIER_HI_EXT: Int = 0x3446;
+    pub(crate) const DMA_BUF_PLANE2_FD_EXT: Int = 0x3278;
+    pub(crate) const DMA_BUF_PLANE2_OFFSET_EXT: Int = 0x3279;
+    pub(crate) const DMA_BUF_PLANE2_PITCH_EXT: Int = 0x327A;
+    pub(crate) const DMA_BUF_PLANE2_MODIFIER_LO_EXT: Int = 0x3447;
+    pub(crate) const DMA_BUF_PLANE2_MODIFIER_HI_EXT: Int = 0x3448;
+    pub(crate) const DMA_BUF_PLANE3_FD_EXT: Int = 0x3440;
+    pub(crate) const DMA_BUF_PLANE3_OFFSET_EXT: Int = 0x3441;
+    pub(crate) const DMA_BUF_PLANE3_PITCH_EXT: Int = 0x3442;
+    pub(crate) const DMA_BUF_PLANE3_MODIFIER_LO_EXT: Int = 0x3449;
+    pub(crate) const DMA_BUF_PLANE3_MODIFIER_HI_EXT: Int = 0x344A;
+
+    // (fd, offset, pitch, modifier lo, modifier hi) attributes of each plane
+    pub(crate) const DMA_BUF_PLANE_ATTRIBS: [[Int; 5]; 4] = [
+        [
+            DMA_BUF_PLANE0_FD_EXT,
+            DMA_BUF_PLANE0_OFFSET_EXT,
+            DMA_BUF_PLANE0_PITCH_EXT,
+            DMA_BUF_PLANE0_MODIFIER_LO_EXT,
+            DMA_BUF_PLANE0_MODIFIER_HI_EXT,
+        ],
+        [
+            DMA_BUF_PLANE1_FD_EXT,
+            DMA_BUF_PLANE1_OFFSET_EXT,
+            DMA_BUF_PLANE1_PITCH_EXT,
+            DMA_BUF_PLANE1_MODIFIER_LO_EXT,
+            DMA_BUF_PLANE1_MODIFIER_HI_EXT,
+        ],
+        [
+            DMA_BUF_PLANE2_FD_EXT,
+            DMA_BUF_PLANE2_OFFSET_EXT,
+            DMA_BUF_PLANE2_PITCH_EXT,
+            DMA_BUF_PLANE2_MODIFIER_LO_EXT,
+            DMA_BUF_PLANE2_MODIFIER_HI_EXT,
+        ],
+        [
+            DMA_BUF_PLANE3_FD_EXT,
+            DMA_BUF_PLANE3_OFFSET_EXT,
+            DMA_BUF_PLANE3_PITCH_EXT,
+            DMA_BUF_PLANE3_MODIFIER_LO_EXT,
+            DMA_BUF_PLANE3_MODIFIER_HI_EXT,
+        ],
+    ];
+
+    const fn fourcc(code: &[u8; 4]) -> u32 {
+        code[0] as u32 | (code[1] as u32) << 8 | (code[2] as u32) << 16 | (code[3] as u32) << 24
+    }
+
+    pub(crate) const DRM_FORMAT_XRGB8888: u32 = fourcc(b"XR24");
+    pub(crate) const DRM_FORMAT_ARGB8888: u32 = fourcc(b"AR24");
+    pub(crate) const DRM_FORMAT_XBGR8888: u32 = fourcc(b"XB24");
+    pub(crate) const DRM_FORMAT_ABGR8888: u32 = fourcc(b"AB24");
+    pub(crate) const DRM_FORMAT_R8: u32 = fourcc(b"R8  ");
+    pub(crate) const DRM_FORMAT_GR88: u32 = fourcc(b"GR88");
+    pub(crate) const DRM_FORMAT_R16: u32 = fourcc(b"R16 ");
+    pub(crate) const DRM_FORMAT_GR1616: u32 = fourcc(b"GR32");
+    pub(crate) const DRM_FORMAT_NV12: u32 = fourcc(b"NV12");
+    pub(crate) const DRM_FORMAT_P010: u32 = fourcc(b"P010");
+
+    pub(crate) const DRM_FORMAT_MOD_LINEAR: u64 = 0;
 
     // GLAPI void APIENTRY glEGLImageTargetTexture2DOES (GLenum target, GLeglImageOES image);
 
@@ -40,6 +104,28 @@ mod imp {
         }
     }
 
+    // struct dma_buf_sync flags, and _IOW('b', 0, struct dma_buf_sync)
+    pub(crate) const DMA_BUF_SYNC_READ: u64 = 1;
+    pub(crate) const DMA_BUF_SYNC_START: u64 = 0;
+    pub(crate) const DMA_BUF_SYNC_END: u64 = 1 << 2;
+    pub(crate) const DMA_BUF_IOCTL_SYNC: u64 = 0x4008_6200;
+
+    pub(crate) const SYNC_NATIVE_FENCE_ANDROID: Enum = 0x3144;
+    pub(crate) const SYNC_NATIVE_FENCE_FD_ANDROID: Int = 0x3145;
+
+    // EGLint eglDupNativeFenceFDANDROID(EGLDisplay dpy, EGLSyncKHR sync);
+
+    pub(crate) type DupNativeFenceFdAndroidFn =
+        extern "C" fn(*mut std::ffi::c_void, *mut std::ffi::c_void) -> Int;
+
+    pub(crate) fn dup_native_fence_fd_android() -> Option<DupNativeFenceFdAndroidFn> {
+        unsafe {
+            egl()
+                .get_proc_address("eglDupNativeFenceFDANDROID")
+                .map(|f| std::mem::transmute::<_, DupNativeFenceFdAndroidFn>(f))
+        }
+    }
+
     pub(crate) fn no_context() -> Context {
         unsafe { Context::from_ptr(NO_CONTEXT) }
     }
@@ -52,27 +138,110 @@ mod imp {
 #[cfg(not(feature = "bindings"))]
 pub(crate) use imp::*;
 
+pub const RDW_DMABUF_MAX_PLANES: usize = 4;
+
+/// The modifier of a buffer with an implicit layout.
+pub const DRM_FORMAT_MOD_INVALID: u64 = 0x00ff_ffff_ffff_ffff;
+
+/// RdwDmabufPlane:
+/// @fd: DMABUF fd, or -1 if the plane is unused.
+/// @modifier: the format modifier, or DRM_FORMAT_MOD_INVALID if implicit.
+///
+/// A plane of a DMABUF scanout. Planes may share the same fd.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct RdwDmabufPlane {
+    pub fd: RawFd,
+    pub offset: u32,
+    pub stride: u32,
+    pub modifier: u64,
+}
+
+impl Default for RdwDmabufPlane {
+    fn default() -> Self {
+        Self {
+            fd: -1,
+            offset: 0,
+            stride: 0,
+            modifier: DRM_FORMAT_MOD_INVALID,
+        }
+    }
+}
+
 /// RdwDmabufScanout:
-/// @fd: DMABUF fd, ownership is taken.
+/// @n_planes: the number of planes in use.
+/// @planes: the DMABUF planes, fds ownership is taken.
+/// @acquire_fd: a sync-fd to wait for before reading the planes, or -1. Ownership is taken.
 ///
-/// A DMABUF file descriptor along with the associated details.
+/// DMABUF file descriptors along with the associated details.
+///
+/// This replaces the single-plane layout, where @stride, @modifier and @fd were
+/// fields of the scanout: this breaks the ABI, C callers must be rebuilt and set
+/// them in the first plane, with @n_planes set to 1.
 #[derive(Debug)]
 #[repr(C)]
 pub struct RdwDmabufScanout {
     pub width: u32,
     pub height: u32,
-    pub stride: u32,
     pub fourcc: u32,
-    pub modifier: u64,
-    pub fd: RawFd,
+    pub n_planes: u32,
+    pub planes: [RdwDmabufPlane; RDW_DMABUF_MAX_PLANES],
     pub y0_top: bool,
+    pub acquire_fd: RawFd,
+}
+
+impl RdwDmabufScanout {
+    /// A single-plane scanout.
+    pub fn new(
+        width: u32,
+        height: u32,
+        stride: u32,
+        fourcc: u32,
+        modifier: u64,
+        y0_top: bool,
+        fd: RawFd,
+    ) -> Self {
+        let mut planes = [RdwDmabufPlane::default(); RDW_DMABUF_MAX_PLANES];
+        planes[0] = RdwDmabufPlane {
+            fd,
+            offset: 0,
+            stride,
+            modifier,
+        };
+        Self {
+            width,
+            height,
+            fourcc,
+            n_planes: 1,
+            planes,
+            y0_top,
+            acquire_fd: -1,
+        }
+    }
+
+    pub fn planes(&self) -> &[RdwDmabufPlane] {
+        let n = (self.n_planes as usize).min(RDW_DMABUF_MAX_PLANES);
+        &self.planes[..n]
+    }
 }
 
 impl Drop for RdwDmabufScanout {
     fn drop(&mut self) {
-        if self.fd >= 0 {
+        // the unused planes may not be initialized
+        let mut fds: Vec<_> = self
+            .planes()
+            .iter()
+            .map(|p| p.fd)
+            .filter(|fd| *fd >= 0)
+            .collect();
+        fds.sort_unstable();
+        fds.dedup();
+        if self.acquire_fd >= 0 {
+            fds.push(self.acquire_fd);
+        }
+        for fd in fds {
             unsafe {
-                libc::close(self.fd);
+                libc::close(fd);
             }
         }
     }
@@ -3,10 +3,14 @@ use gst::prelude::*;
 use gst_audio::prelude::*;
 use std::{collections::HashMap, default::Default, error::Error};
 
+use crate::{Recorder, RecorderAudio};
+
 #[derive(Debug)]
 struct GstAudioOut {
     pipeline: gst::Pipeline,
     src: gst_app::AppSrc,
+    caps: String,
+    recorder: Option<RecorderAudio>,
 }
 
 impl GstAudioOut {
@@ -19,7 +23,12 @@ impl GstAudioOut {
             .unwrap()
             .dynamic_cast::<gst_app::AppSrc>()
             .unwrap();
-        Ok(Self { pipeline, src })
+        Ok(Self {
+            pipeline,
+            src,
+            caps: caps.to_string(),
+            recorder: None,
+        })
     }
 }
 
@@ -111,10 +120,30 @@ impl GstAudio {
         Ok(())
     }
 
+    pub fn set_recorder_out(
+        &mut self,
+        id: u64,
+        recorder: Option<&Recorder>,
+    ) -> Result<(), Box<dyn Error>> {
+        let out = self
+            .out
+            .get_mut(&id)
+            .ok_or_else(|| format!("Stream not found: {}", id))?;
+        out.recorder = match recorder {
+            Some(recorder) => Some(recorder.add_audio(&out.caps)?),
+            None => None,
+        };
+        Ok(())
+    }
+
     pub fn write_out(&mut self, id: u64, data: Vec<u8>) -> Result<(), Box<dyn Error>> {
-        self.get_out(id)?
-            .src
-            .push_buffer(gst::Buffer::from_slice(data))?;
+        let out = self.get_out(id)?;
+        if let Some(recorder) = &out.recorder {
+            if let Err(e) = recorder.push(&data) {
+                log::warn!("Failed to record audio: {}", e);
+            }
+        }
+        out.src.push_buffer(gst::Buffer::from_slice(data))?;
         Ok(())
     }
 
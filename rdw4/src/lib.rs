@@ -15,6 +15,9 @@ mod egl;
 mod error;
 mod gstaudio;
 mod keymap;
+mod model;
+mod monitors;
+mod recorder;
 mod usbredir;
 #[cfg(windows)]
 mod win32;
@@ -25,10 +28,13 @@ mod util;
 pub use content_provider::ContentProvider;
 pub use display::*;
 #[cfg(unix)]
-pub use egl::RdwDmabufScanout;
+pub use egl::{RdwDmabufPlane, RdwDmabufScanout, RDW_DMABUF_MAX_PLANES};
 pub use error::Error;
 pub use gstaudio::*;
 pub use keymap::*;
+pub use model::{CursorImage, DisplayModel, Geometry, InputEvent, Rect};
+pub use monitors::{MonitorLayout, MonitorManager, WeakMonitorManager};
+pub use recorder::{Recorder, RecorderAudio, RecorderFormat};
 pub use usbredir::{Device as UsbDevice, UsbRedir};
 
 #[cfg(feature = "capi")]
@@ -60,6 +66,155 @@ mod ffi {
         Scroll::static_type().into_glib()
     }
 
+    #[derive(Debug, Eq, PartialEq, Clone, Copy, Enum)]
+    #[enum_type(name = "RdwScalingMode")]
+    #[repr(C)]
+    pub enum ScalingMode {
+        Fit,
+        Fill,
+        Original,
+        IntegerScale,
+        Stretch,
+    }
+
+    pub type RdwScalingMode = <ScalingMode as IntoGlib>::GlibType;
+
+    pub const RDW_SCALING_MODE_FIT: RdwScalingMode = ScalingMode::Fit as i32;
+    pub const RDW_SCALING_MODE_FILL: RdwScalingMode = ScalingMode::Fill as i32;
+    pub const RDW_SCALING_MODE_ORIGINAL: RdwScalingMode = ScalingMode::Original as i32;
+    pub const RDW_SCALING_MODE_INTEGER_SCALE: RdwScalingMode = ScalingMode::IntegerScale as i32;
+    pub const RDW_SCALING_MODE_STRETCH: RdwScalingMode = ScalingMode::Stretch as i32;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_scaling_mode_get_type() -> glib::ffi::GType {
+        ScalingMode::static_type().into_glib()
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Copy, Enum)]
+    #[enum_type(name = "RdwRotation")]
+    #[repr(C)]
+    pub enum Rotation {
+        Normal,
+        Rotate90,
+        Rotate180,
+        Rotate270,
+    }
+
+    pub type RdwRotation = <Rotation as IntoGlib>::GlibType;
+
+    pub const RDW_ROTATION_NORMAL: RdwRotation = Rotation::Normal as i32;
+    pub const RDW_ROTATION_ROTATE90: RdwRotation = Rotation::Rotate90 as i32;
+    pub const RDW_ROTATION_ROTATE180: RdwRotation = Rotation::Rotate180 as i32;
+    pub const RDW_ROTATION_ROTATE270: RdwRotation = Rotation::Rotate270 as i32;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_rotation_get_type() -> glib::ffi::GType {
+        Rotation::static_type().into_glib()
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Copy, Enum)]
+    #[enum_type(name = "RdwPixelFormat")]
+    #[repr(C)]
+    pub enum PixelFormat {
+        Xrgb8888,
+        Argb8888,
+        Argb8888Premultiplied,
+        Xbgr8888,
+        Abgr8888,
+        Abgr8888Premultiplied,
+        Rgb565,
+        Xrgb1555,
+        Xrgb2101010,
+        Argb2101010,
+    }
+
+    pub type RdwPixelFormat = <PixelFormat as IntoGlib>::GlibType;
+
+    pub const RDW_PIXEL_FORMAT_XRGB8888: RdwPixelFormat = PixelFormat::Xrgb8888 as i32;
+    pub const RDW_PIXEL_FORMAT_ARGB8888: RdwPixelFormat = PixelFormat::Argb8888 as i32;
+    pub const RDW_PIXEL_FORMAT_ARGB8888_PREMULTIPLIED: RdwPixelFormat =
+        PixelFormat::Argb8888Premultiplied as i32;
+    pub const RDW_PIXEL_FORMAT_XBGR8888: RdwPixelFormat = PixelFormat::Xbgr8888 as i32;
+    pub const RDW_PIXEL_FORMAT_ABGR8888: RdwPixelFormat = PixelFormat::Abgr8888 as i32;
+    pub const RDW_PIXEL_FORMAT_ABGR8888_PREMULTIPLIED: RdwPixelFormat =
+        PixelFormat::Abgr8888Premultiplied as i32;
+    pub const RDW_PIXEL_FORMAT_RGB565: RdwPixelFormat = PixelFormat::Rgb565 as i32;
+    pub const RDW_PIXEL_FORMAT_XRGB1555: RdwPixelFormat = PixelFormat::Xrgb1555 as i32;
+    pub const RDW_PIXEL_FORMAT_XRGB2101010: RdwPixelFormat = PixelFormat::Xrgb2101010 as i32;
+    pub const RDW_PIXEL_FORMAT_ARGB2101010: RdwPixelFormat = PixelFormat::Argb2101010 as i32;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_pixel_format_get_type() -> glib::ffi::GType {
+        PixelFormat::static_type().into_glib()
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Copy, Enum)]
+    #[enum_type(name = "RdwResizePolicy")]
+    #[repr(C)]
+    pub enum ResizePolicy {
+        None,
+        OnIdle,
+        Immediate,
+        OnFullscreenOnly,
+    }
+
+    pub type RdwResizePolicy = <ResizePolicy as IntoGlib>::GlibType;
+
+    pub const RDW_RESIZE_POLICY_NONE: RdwResizePolicy = ResizePolicy::None as i32;
+    pub const RDW_RESIZE_POLICY_ON_IDLE: RdwResizePolicy = ResizePolicy::OnIdle as i32;
+    pub const RDW_RESIZE_POLICY_IMMEDIATE: RdwResizePolicy = ResizePolicy::Immediate as i32;
+    pub const RDW_RESIZE_POLICY_ON_FULLSCREEN_ONLY: RdwResizePolicy =
+        ResizePolicy::OnFullscreenOnly as i32;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_resize_policy_get_type() -> glib::ffi::GType {
+        ResizePolicy::static_type().into_glib()
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Copy, Enum)]
+    #[enum_type(name = "RdwTouchMode")]
+    #[repr(C)]
+    pub enum TouchMode {
+        PassThrough,
+        EmulateMouse,
+    }
+
+    pub type RdwTouchMode = <TouchMode as IntoGlib>::GlibType;
+
+    pub const RDW_TOUCH_MODE_PASS_THROUGH: RdwTouchMode = TouchMode::PassThrough as i32;
+    pub const RDW_TOUCH_MODE_EMULATE_MOUSE: RdwTouchMode = TouchMode::EmulateMouse as i32;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_touch_mode_get_type() -> glib::ffi::GType {
+        TouchMode::static_type().into_glib()
+    }
+
+    #[derive(Debug, Eq, PartialEq, Clone, Copy, Enum)]
+    #[enum_type(name = "RdwMouseButton")]
+    #[repr(C)]
+    pub enum MouseButton {
+        Left,
+        Middle,
+        Right,
+        Back,
+        Forward,
+        Task,
+    }
+
+    pub type RdwMouseButton = <MouseButton as IntoGlib>::GlibType;
+
+    pub const RDW_MOUSE_BUTTON_LEFT: RdwMouseButton = MouseButton::Left as i32;
+    pub const RDW_MOUSE_BUTTON_MIDDLE: RdwMouseButton = MouseButton::Middle as i32;
+    pub const RDW_MOUSE_BUTTON_RIGHT: RdwMouseButton = MouseButton::Right as i32;
+    pub const RDW_MOUSE_BUTTON_BACK: RdwMouseButton = MouseButton::Back as i32;
+    pub const RDW_MOUSE_BUTTON_FORWARD: RdwMouseButton = MouseButton::Forward as i32;
+    pub const RDW_MOUSE_BUTTON_TASK: RdwMouseButton = MouseButton::Task as i32;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_mouse_button_get_type() -> glib::ffi::GType {
+        MouseButton::static_type().into_glib()
+    }
+
     #[flags(name = "RdwKeyEvent")]
     #[repr(C)] // See https://github.com/bitflags/bitflags/pull/187
     pub enum KeyEvent {
@@ -93,6 +248,28 @@ mod ffi {
     pub unsafe extern "C" fn rdw_grab_get_type() -> glib::ffi::GType {
         Grab::static_type().into_glib()
     }
+
+    #[flags(name = "RdwStylusFlags")]
+    #[repr(C)]
+    pub enum StylusFlags {
+        CONTACT = 0b0000_0001,
+        ERASER = 0b0000_0010,
+        BARREL = 0b0000_0100,
+        SECONDARY_BARREL = 0b0000_1000,
+    }
+
+    pub type RdwStylusFlags = <StylusFlags as IntoGlib>::GlibType;
+
+    pub const RDW_STYLUS_FLAGS_CONTACT: RdwStylusFlags = StylusFlags::CONTACT.bits();
+    pub const RDW_STYLUS_FLAGS_ERASER: RdwStylusFlags = StylusFlags::ERASER.bits();
+    pub const RDW_STYLUS_FLAGS_BARREL: RdwStylusFlags = StylusFlags::BARREL.bits();
+    pub const RDW_STYLUS_FLAGS_SECONDARY_BARREL: RdwStylusFlags =
+        StylusFlags::SECONDARY_BARREL.bits();
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rdw_stylus_flags_get_type() -> glib::ffi::GType {
+        StylusFlags::static_type().into_glib()
+    }
 }
 
 /// cbindgen:ignore
@@ -112,6 +289,79 @@ mod ffi {
         pub fn rdw_scroll_get_type() -> glib::ffi::GType;
     }
 
+    pub type RdwScalingMode = c_int;
+
+    pub const RDW_SCALING_MODE_FIT: RdwScalingMode = 0;
+    pub const RDW_SCALING_MODE_FILL: RdwScalingMode = 1;
+    pub const RDW_SCALING_MODE_ORIGINAL: RdwScalingMode = 2;
+    pub const RDW_SCALING_MODE_INTEGER_SCALE: RdwScalingMode = 3;
+    pub const RDW_SCALING_MODE_STRETCH: RdwScalingMode = 4;
+
+    extern "C" {
+        pub fn rdw_scaling_mode_get_type() -> glib::ffi::GType;
+    }
+
+    pub type RdwRotation = c_int;
+
+    pub const RDW_ROTATION_NORMAL: RdwRotation = 0;
+    pub const RDW_ROTATION_ROTATE90: RdwRotation = 1;
+    pub const RDW_ROTATION_ROTATE180: RdwRotation = 2;
+    pub const RDW_ROTATION_ROTATE270: RdwRotation = 3;
+
+    extern "C" {
+        pub fn rdw_rotation_get_type() -> glib::ffi::GType;
+    }
+
+    pub type RdwPixelFormat = c_int;
+
+    pub const RDW_PIXEL_FORMAT_XRGB8888: RdwPixelFormat = 0;
+    pub const RDW_PIXEL_FORMAT_ARGB8888: RdwPixelFormat = 1;
+    pub const RDW_PIXEL_FORMAT_ARGB8888_PREMULTIPLIED: RdwPixelFormat = 2;
+    pub const RDW_PIXEL_FORMAT_XBGR8888: RdwPixelFormat = 3;
+    pub const RDW_PIXEL_FORMAT_ABGR8888: RdwPixelFormat = 4;
+    pub const RDW_PIXEL_FORMAT_ABGR8888_PREMULTIPLIED: RdwPixelFormat = 5;
+    pub const RDW_PIXEL_FORMAT_RGB565: RdwPixelFormat = 6;
+    pub const RDW_PIXEL_FORMAT_XRGB1555: RdwPixelFormat = 7;
+    pub const RDW_PIXEL_FORMAT_XRGB2101010: RdwPixelFormat = 8;
+    pub const RDW_PIXEL_FORMAT_ARGB2101010: RdwPixelFormat = 9;
+
+    extern "C" {
+        pub fn rdw_pixel_format_get_type() -> glib::ffi::GType;
+    }
+
+    pub type RdwResizePolicy = c_int;
+
+    pub const RDW_RESIZE_POLICY_NONE: RdwResizePolicy = 0;
+    pub const RDW_RESIZE_POLICY_ON_IDLE: RdwResizePolicy = 1;
+    pub const RDW_RESIZE_POLICY_IMMEDIATE: RdwResizePolicy = 2;
+    pub const RDW_RESIZE_POLICY_ON_FULLSCREEN_ONLY: RdwResizePolicy = 3;
+
+    extern "C" {
+        pub fn rdw_resize_policy_get_type() -> glib::ffi::GType;
+    }
+
+    pub type RdwTouchMode = c_int;
+
+    pub const RDW_TOUCH_MODE_PASS_THROUGH: RdwTouchMode = 0;
+    pub const RDW_TOUCH_MODE_EMULATE_MOUSE: RdwTouchMode = 1;
+
+    extern "C" {
+        pub fn rdw_touch_mode_get_type() -> glib::ffi::GType;
+    }
+
+    pub type RdwMouseButton = c_int;
+
+    pub const RDW_MOUSE_BUTTON_LEFT: RdwMouseButton = 0;
+    pub const RDW_MOUSE_BUTTON_MIDDLE: RdwMouseButton = 1;
+    pub const RDW_MOUSE_BUTTON_RIGHT: RdwMouseButton = 2;
+    pub const RDW_MOUSE_BUTTON_BACK: RdwMouseButton = 3;
+    pub const RDW_MOUSE_BUTTON_FORWARD: RdwMouseButton = 4;
+    pub const RDW_MOUSE_BUTTON_TASK: RdwMouseButton = 5;
+
+    extern "C" {
+        pub fn rdw_mouse_button_get_type() -> glib::ffi::GType;
+    }
+
     pub type RdwKeyEvent = c_uint;
 
     pub const RDW_KEY_EVENT_PRESS: RdwKeyEvent = 0b0000_0001;
@@ -129,6 +379,17 @@ mod ffi {
     extern "C" {
         pub fn rdw_grab_get_type() -> glib::ffi::GType;
     }
+
+    pub type RdwStylusFlags = c_uint;
+
+    pub const RDW_STYLUS_FLAGS_CONTACT: RdwStylusFlags = 0b0000_0001;
+    pub const RDW_STYLUS_FLAGS_ERASER: RdwStylusFlags = 0b0000_0010;
+    pub const RDW_STYLUS_FLAGS_BARREL: RdwStylusFlags = 0b0000_0100;
+    pub const RDW_STYLUS_FLAGS_SECONDARY_BARREL: RdwStylusFlags = 0b0000_1000;
+
+    extern "C" {
+        pub fn rdw_stylus_flags_get_type() -> glib::ffi::GType;
+    }
 }
 
 /// cbindgen:ignore
@@ -206,6 +467,599 @@ impl ToValue for Scroll {
     }
 }
 
+/// cbindgen:ignore
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+#[repr(C)]
+pub enum ScalingMode {
+    Fit,
+    Fill,
+    Original,
+    IntegerScale,
+    Stretch,
+    __Unknown(i32),
+}
+
+impl IntoGlib for ScalingMode {
+    type GlibType = ffi::RdwScalingMode;
+
+    fn into_glib(self) -> ffi::RdwScalingMode {
+        match self {
+            ScalingMode::Fit => ffi::RDW_SCALING_MODE_FIT,
+            ScalingMode::Fill => ffi::RDW_SCALING_MODE_FILL,
+            ScalingMode::Original => ffi::RDW_SCALING_MODE_ORIGINAL,
+            ScalingMode::IntegerScale => ffi::RDW_SCALING_MODE_INTEGER_SCALE,
+            ScalingMode::Stretch => ffi::RDW_SCALING_MODE_STRETCH,
+            ScalingMode::__Unknown(v) => v,
+        }
+    }
+}
+
+impl FromGlib<ffi::RdwScalingMode> for ScalingMode {
+    unsafe fn from_glib(value: ffi::RdwScalingMode) -> Self {
+        match value {
+            ffi::RDW_SCALING_MODE_FIT => Self::Fit,
+            ffi::RDW_SCALING_MODE_FILL => Self::Fill,
+            ffi::RDW_SCALING_MODE_ORIGINAL => Self::Original,
+            ffi::RDW_SCALING_MODE_INTEGER_SCALE => Self::IntegerScale,
+            ffi::RDW_SCALING_MODE_STRETCH => Self::Stretch,
+            value => Self::__Unknown(value),
+        }
+    }
+}
+
+impl StaticType for ScalingMode {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_scaling_mode_get_type()) }
+    }
+}
+
+impl ValueType for ScalingMode {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for ScalingMode {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_enum(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for ScalingMode {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_enum(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl std::default::Default for ScalingMode {
+    fn default() -> Self {
+        Self::Fit
+    }
+}
+
+/// cbindgen:ignore
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+#[repr(C)]
+pub enum Rotation {
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    __Unknown(i32),
+}
+
+impl IntoGlib for Rotation {
+    type GlibType = ffi::RdwRotation;
+
+    fn into_glib(self) -> ffi::RdwRotation {
+        match self {
+            Rotation::Normal => ffi::RDW_ROTATION_NORMAL,
+            Rotation::Rotate90 => ffi::RDW_ROTATION_ROTATE90,
+            Rotation::Rotate180 => ffi::RDW_ROTATION_ROTATE180,
+            Rotation::Rotate270 => ffi::RDW_ROTATION_ROTATE270,
+            Rotation::__Unknown(v) => v,
+        }
+    }
+}
+
+impl FromGlib<ffi::RdwRotation> for Rotation {
+    unsafe fn from_glib(value: ffi::RdwRotation) -> Self {
+        match value {
+            ffi::RDW_ROTATION_NORMAL => Self::Normal,
+            ffi::RDW_ROTATION_ROTATE90 => Self::Rotate90,
+            ffi::RDW_ROTATION_ROTATE180 => Self::Rotate180,
+            ffi::RDW_ROTATION_ROTATE270 => Self::Rotate270,
+            value => Self::__Unknown(value),
+        }
+    }
+}
+
+impl StaticType for Rotation {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_rotation_get_type()) }
+    }
+}
+
+impl ValueType for Rotation {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for Rotation {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_enum(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for Rotation {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_enum(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl std::default::Default for Rotation {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// cbindgen:ignore
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+#[repr(C)]
+pub enum PixelFormat {
+    Xrgb8888,
+    Argb8888,
+    Argb8888Premultiplied,
+    Xbgr8888,
+    Abgr8888,
+    Abgr8888Premultiplied,
+    Rgb565,
+    Xrgb1555,
+    Xrgb2101010,
+    Argb2101010,
+    __Unknown(i32),
+}
+
+impl IntoGlib for PixelFormat {
+    type GlibType = ffi::RdwPixelFormat;
+
+    fn into_glib(self) -> ffi::RdwPixelFormat {
+        match self {
+            PixelFormat::Xrgb8888 => ffi::RDW_PIXEL_FORMAT_XRGB8888,
+            PixelFormat::Argb8888 => ffi::RDW_PIXEL_FORMAT_ARGB8888,
+            PixelFormat::Argb8888Premultiplied => ffi::RDW_PIXEL_FORMAT_ARGB8888_PREMULTIPLIED,
+            PixelFormat::Xbgr8888 => ffi::RDW_PIXEL_FORMAT_XBGR8888,
+            PixelFormat::Abgr8888 => ffi::RDW_PIXEL_FORMAT_ABGR8888,
+            PixelFormat::Abgr8888Premultiplied => ffi::RDW_PIXEL_FORMAT_ABGR8888_PREMULTIPLIED,
+            PixelFormat::Rgb565 => ffi::RDW_PIXEL_FORMAT_RGB565,
+            PixelFormat::Xrgb1555 => ffi::RDW_PIXEL_FORMAT_XRGB1555,
+            PixelFormat::Xrgb2101010 => ffi::RDW_PIXEL_FORMAT_XRGB2101010,
+            PixelFormat::Argb2101010 => ffi::RDW_PIXEL_FORMAT_ARGB2101010,
+            PixelFormat::__Unknown(v) => v,
+        }
+    }
+}
+
+impl FromGlib<ffi::RdwPixelFormat> for PixelFormat {
+    unsafe fn from_glib(value: ffi::RdwPixelFormat) -> Self {
+        match value {
+            ffi::RDW_PIXEL_FORMAT_XRGB8888 => Self::Xrgb8888,
+            ffi::RDW_PIXEL_FORMAT_ARGB8888 => Self::Argb8888,
+            ffi::RDW_PIXEL_FORMAT_ARGB8888_PREMULTIPLIED => Self::Argb8888Premultiplied,
+            ffi::RDW_PIXEL_FORMAT_XBGR8888 => Self::Xbgr8888,
+            ffi::RDW_PIXEL_FORMAT_ABGR8888 => Self::Abgr8888,
+            ffi::RDW_PIXEL_FORMAT_ABGR8888_PREMULTIPLIED => Self::Abgr8888Premultiplied,
+            ffi::RDW_PIXEL_FORMAT_RGB565 => Self::Rgb565,
+            ffi::RDW_PIXEL_FORMAT_XRGB1555 => Self::Xrgb1555,
+            ffi::RDW_PIXEL_FORMAT_XRGB2101010 => Self::Xrgb2101010,
+            ffi::RDW_PIXEL_FORMAT_ARGB2101010 => Self::Argb2101010,
+            value => Self::__Unknown(value),
+        }
+    }
+}
+
+impl StaticType for PixelFormat {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_pixel_format_get_type()) }
+    }
+}
+
+impl ValueType for PixelFormat {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for PixelFormat {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_enum(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for PixelFormat {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_enum(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl std::default::Default for PixelFormat {
+    fn default() -> Self {
+        Self::Xrgb8888
+    }
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgb565 | Self::Xrgb1555 => 2,
+            _ => 4,
+        }
+    }
+
+    pub fn has_alpha(self) -> bool {
+        matches!(
+            self,
+            Self::Argb8888
+                | Self::Argb8888Premultiplied
+                | Self::Abgr8888
+                | Self::Abgr8888Premultiplied
+                | Self::Argb2101010
+        )
+    }
+
+    // convert pixels to premultiplied BGRA, aka gdk::MemoryFormat::B8g8r8a8Premultiplied
+    pub(crate) fn to_bgra_premultiplied(self, src: &[u8], dst: &mut [u8]) {
+        let premultiply = |c: u8, a: u8| ((c as u16 * a as u16 + 127) / 255) as u8;
+        let expand5 = |c: u16| (c << 3 | c >> 2) as u8;
+        let expand6 = |c: u16| (c << 2 | c >> 4) as u8;
+
+        let bpp = self.bytes_per_pixel();
+        for (s, d) in src.chunks_exact(bpp).zip(dst.chunks_exact_mut(4)) {
+            let (b, g, r, a) = match self {
+                Self::Argb8888 => (s[0], s[1], s[2], s[3]),
+                Self::Argb8888Premultiplied => {
+                    d.copy_from_slice(s);
+                    continue;
+                }
+                Self::Xbgr8888 => (s[2], s[1], s[0], 0xff),
+                Self::Abgr8888 => (s[2], s[1], s[0], s[3]),
+                Self::Abgr8888Premultiplied => {
+                    d.copy_from_slice(&[s[2], s[1], s[0], s[3]]);
+                    continue;
+                }
+                Self::Rgb565 => {
+                    let v = u16::from_le_bytes([s[0], s[1]]);
+                    (
+                        expand5(v & 0x1f),
+                        expand6((v >> 5) & 0x3f),
+                        expand5((v >> 11) & 0x1f),
+                        0xff,
+                    )
+                }
+                Self::Xrgb1555 => {
+                    let v = u16::from_le_bytes([s[0], s[1]]);
+                    (
+                        expand5(v & 0x1f),
+                        expand5((v >> 5) & 0x1f),
+                        expand5((v >> 10) & 0x1f),
+                        0xff,
+                    )
+                }
+                Self::Xrgb2101010 | Self::Argb2101010 => {
+                    let v = u32::from_le_bytes([s[0], s[1], s[2], s[3]]);
+                    let a = if self == Self::Argb2101010 {
+                        ((v >> 30) * 0x55) as u8
+                    } else {
+                        0xff
+                    };
+                    ((v >> 2) as u8, (v >> 12) as u8, (v >> 22) as u8, a)
+                }
+                _ => (s[0], s[1], s[2], 0xff),
+            };
+            d.copy_from_slice(&[premultiply(b, a), premultiply(g, a), premultiply(r, a), a]);
+        }
+    }
+}
+
+/// cbindgen:ignore
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+#[repr(C)]
+pub enum ResizePolicy {
+    None,
+    OnIdle,
+    Immediate,
+    OnFullscreenOnly,
+    __Unknown(i32),
+}
+
+impl IntoGlib for ResizePolicy {
+    type GlibType = ffi::RdwResizePolicy;
+
+    fn into_glib(self) -> ffi::RdwResizePolicy {
+        match self {
+            ResizePolicy::None => ffi::RDW_RESIZE_POLICY_NONE,
+            ResizePolicy::OnIdle => ffi::RDW_RESIZE_POLICY_ON_IDLE,
+            ResizePolicy::Immediate => ffi::RDW_RESIZE_POLICY_IMMEDIATE,
+            ResizePolicy::OnFullscreenOnly => ffi::RDW_RESIZE_POLICY_ON_FULLSCREEN_ONLY,
+            ResizePolicy::__Unknown(v) => v,
+        }
+    }
+}
+
+impl FromGlib<ffi::RdwResizePolicy> for ResizePolicy {
+    unsafe fn from_glib(value: ffi::RdwResizePolicy) -> Self {
+        match value {
+            ffi::RDW_RESIZE_POLICY_NONE => Self::None,
+            ffi::RDW_RESIZE_POLICY_ON_IDLE => Self::OnIdle,
+            ffi::RDW_RESIZE_POLICY_IMMEDIATE => Self::Immediate,
+            ffi::RDW_RESIZE_POLICY_ON_FULLSCREEN_ONLY => Self::OnFullscreenOnly,
+            value => Self::__Unknown(value),
+        }
+    }
+}
+
+impl StaticType for ResizePolicy {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_resize_policy_get_type()) }
+    }
+}
+
+impl ValueType for ResizePolicy {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for ResizePolicy {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_enum(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for ResizePolicy {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_enum(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl std::default::Default for ResizePolicy {
+    fn default() -> Self {
+        Self::OnIdle
+    }
+}
+
+/// cbindgen:ignore
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+#[repr(C)]
+pub enum TouchMode {
+    PassThrough,
+    EmulateMouse,
+    __Unknown(i32),
+}
+
+impl IntoGlib for TouchMode {
+    type GlibType = ffi::RdwTouchMode;
+
+    fn into_glib(self) -> ffi::RdwTouchMode {
+        match self {
+            TouchMode::PassThrough => ffi::RDW_TOUCH_MODE_PASS_THROUGH,
+            TouchMode::EmulateMouse => ffi::RDW_TOUCH_MODE_EMULATE_MOUSE,
+            TouchMode::__Unknown(v) => v,
+        }
+    }
+}
+
+impl FromGlib<ffi::RdwTouchMode> for TouchMode {
+    unsafe fn from_glib(value: ffi::RdwTouchMode) -> Self {
+        match value {
+            ffi::RDW_TOUCH_MODE_PASS_THROUGH => Self::PassThrough,
+            ffi::RDW_TOUCH_MODE_EMULATE_MOUSE => Self::EmulateMouse,
+            value => Self::__Unknown(value),
+        }
+    }
+}
+
+impl StaticType for TouchMode {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_touch_mode_get_type()) }
+    }
+}
+
+impl ValueType for TouchMode {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for TouchMode {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_enum(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for TouchMode {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_enum(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl std::default::Default for TouchMode {
+    fn default() -> Self {
+        Self::EmulateMouse
+    }
+}
+
+/// cbindgen:ignore
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[non_exhaustive]
+#[repr(C)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    Back,
+    Forward,
+    Task,
+    __Unknown(i32),
+}
+
+impl IntoGlib for MouseButton {
+    type GlibType = ffi::RdwMouseButton;
+
+    fn into_glib(self) -> ffi::RdwMouseButton {
+        match self {
+            MouseButton::Left => ffi::RDW_MOUSE_BUTTON_LEFT,
+            MouseButton::Middle => ffi::RDW_MOUSE_BUTTON_MIDDLE,
+            MouseButton::Right => ffi::RDW_MOUSE_BUTTON_RIGHT,
+            MouseButton::Back => ffi::RDW_MOUSE_BUTTON_BACK,
+            MouseButton::Forward => ffi::RDW_MOUSE_BUTTON_FORWARD,
+            MouseButton::Task => ffi::RDW_MOUSE_BUTTON_TASK,
+            MouseButton::__Unknown(v) => v,
+        }
+    }
+}
+
+impl FromGlib<ffi::RdwMouseButton> for MouseButton {
+    unsafe fn from_glib(value: ffi::RdwMouseButton) -> Self {
+        match value {
+            ffi::RDW_MOUSE_BUTTON_LEFT => Self::Left,
+            ffi::RDW_MOUSE_BUTTON_MIDDLE => Self::Middle,
+            ffi::RDW_MOUSE_BUTTON_RIGHT => Self::Right,
+            ffi::RDW_MOUSE_BUTTON_BACK => Self::Back,
+            ffi::RDW_MOUSE_BUTTON_FORWARD => Self::Forward,
+            ffi::RDW_MOUSE_BUTTON_TASK => Self::Task,
+            value => Self::__Unknown(value),
+        }
+    }
+}
+
+impl StaticType for MouseButton {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_mouse_button_get_type()) }
+    }
+}
+
+impl ValueType for MouseButton {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for MouseButton {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_enum(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for MouseButton {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_enum(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl MouseButton {
+    /// The button for a GDK button number, if known.
+    pub fn from_gdk(button: u32) -> Option<Self> {
+        match button {
+            1 => Some(Self::Left),
+            2 => Some(Self::Middle),
+            3 => Some(Self::Right),
+            // X11 and Windows side buttons, BTN_SIDE/BTN_EXTRA on Wayland
+            8 | 97 => Some(Self::Back),
+            9 | 112 => Some(Self::Forward),
+            // BTN_FORWARD, BTN_BACK and BTN_TASK on Wayland
+            10 => Some(Self::Forward),
+            11 => Some(Self::Back),
+            12 => Some(Self::Task),
+            _ => None,
+        }
+    }
+}
+
 bitflags! {
     #[repr(transparent)]
     pub struct Grab: u32 {
@@ -330,6 +1184,73 @@ impl ToValue for KeyEvent {
     }
 }
 
+bitflags! {
+    #[repr(transparent)]
+    pub struct StylusFlags: u32 {
+        const CONTACT = ffi::RDW_STYLUS_FLAGS_CONTACT;
+        const ERASER = ffi::RDW_STYLUS_FLAGS_ERASER;
+        const BARREL = ffi::RDW_STYLUS_FLAGS_BARREL;
+        const SECONDARY_BARREL = ffi::RDW_STYLUS_FLAGS_SECONDARY_BARREL;
+    }
+}
+
+impl IntoGlib for StylusFlags {
+    type GlibType = ffi::RdwStylusFlags;
+
+    fn into_glib(self) -> ffi::RdwStylusFlags {
+        self.bits()
+    }
+}
+
+impl FromGlib<ffi::RdwStylusFlags> for StylusFlags {
+    unsafe fn from_glib(value: ffi::RdwStylusFlags) -> Self {
+        StylusFlags::from_bits_truncate(value)
+    }
+}
+
+impl StaticType for StylusFlags {
+    fn static_type() -> Type {
+        unsafe { from_glib(ffi::rdw_stylus_flags_get_type()) }
+    }
+}
+
+impl ValueType for StylusFlags {
+    type Type = Self;
+}
+
+unsafe impl<'a> FromValue<'a> for StylusFlags {
+    type Checker = GenericValueTypeChecker<Self>;
+
+    unsafe fn from_value(value: &'a Value) -> Self {
+        from_glib(glib::gobject_ffi::g_value_get_flags(
+            ToGlibPtr::to_glib_none(value).0,
+        ))
+    }
+}
+
+impl ToValue for StylusFlags {
+    fn to_value(&self) -> Value {
+        let mut value = Value::for_value_type::<Self>();
+        unsafe {
+            glib::gobject_ffi::g_value_set_flags(
+                ToGlibPtrMut::to_glib_none_mut(&mut value).0,
+                IntoGlib::into_glib(*self),
+            )
+        }
+        value
+    }
+
+    fn value_type(&self) -> Type {
+        <Self as StaticType>::static_type()
+    }
+}
+
+impl std::default::Default for StylusFlags {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 /// cbindgen:ignore
 // from https://github.com/rust-lang/log/issues/421#issuecomment-990617341
 #[cfg(not(feature = "bindings"))]
@@ -0,0 +1,785 @@
+use crate::{KeyEvent, MouseButton, PixelFormat, Rotation, ScalingMode, Scroll, StylusFlags};
+
+pub(crate) const ZOOM_MIN: f64 = 0.1;
+pub(crate) const ZOOM_MAX: f64 = 16.0;
+pub(crate) const ZOOM_STEP: f64 = 1.25;
+
+/// A rectangle, in remote display or widget device pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let (x0, y0) = (self.x.max(other.x), self.y.max(other.y));
+        let x1 = (self.x + self.width).min(other.x + other.width);
+        let y1 = (self.y + self.height).min(other.y + other.height);
+        if x1 > x0 && y1 > y0 {
+            Some(Rect::new(x0, y0, x1 - x0, y1 - y0))
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_point(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// The widget area the remote display is rendered to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+    /// size in logical pixels
+    pub width: i32,
+    pub height: i32,
+    pub scale_factor: i32,
+    /// scroll offsets in logical pixels, when the remote display is larger than the widget
+    pub scroll: (Option<f64>, Option<f64>),
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            scale_factor: 1,
+            scroll: (None, None),
+        }
+    }
+}
+
+/// A client-side cursor image, in premultiplied BGRA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+    pub width: usize,
+    pub height: usize,
+    pub hot_x: usize,
+    pub hot_y: usize,
+    pub data: Vec<u8>,
+}
+
+impl CursorImage {
+    // a hidden cursor
+    pub fn is_transparent(&self) -> bool {
+        self.data.chunks_exact(4).all(|px| px[3] == 0)
+    }
+}
+
+/// The input events a `Display` emits as signals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    Key {
+        keyval: u32,
+        keycode: u32,
+        event: KeyEvent,
+    },
+    Motion {
+        x: f64,
+        y: f64,
+    },
+    MotionRelative {
+        dx: f64,
+        dy: f64,
+    },
+    MousePress(MouseButton),
+    MouseRelease(MouseButton),
+    ScrollDiscrete(Scroll),
+    // in wheel clicks, and in 120ths of a click
+    ScrollSmooth {
+        dx: f64,
+        dy: f64,
+        v120_x: i32,
+        v120_y: i32,
+    },
+    Stylus {
+        x: f64,
+        y: f64,
+        pressure: f64,
+        tilt_x: f64,
+        tilt_y: f64,
+        rotation: f64,
+        flags: StylusFlags,
+    },
+    // touch slot and remote position
+    TouchBegin {
+        slot: u32,
+        x: f64,
+        y: f64,
+    },
+    TouchUpdate {
+        slot: u32,
+        x: f64,
+        y: f64,
+    },
+    TouchEnd(u32),
+    TouchCancel(u32),
+}
+
+/// The remote display state, independent of GTK.
+///
+/// A `Display` widget renders it and translates its input with it, but it can
+/// be driven headlessly as well.
+#[derive(Debug)]
+pub struct DisplayModel {
+    // The remote display size, ex: 1024x768
+    display_size: Option<(usize, usize)>,
+    format: PixelFormat,
+    // client copy of the remote display
+    framebuffer: Vec<u8>,
+    // remote display areas changed since the last frame
+    damage: Vec<Rect>,
+    cursor: Option<CursorImage>,
+    // position of cursor when drawn by client
+    cursor_position: Option<(usize, usize)>,
+    mouse_absolute: bool,
+    scaling_mode: ScalingMode,
+    rotation: Rotation,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    zoom_level: f64,
+    // whether the remote can be resized
+    resize_supported: bool,
+    // the remote sizes, empty if arbitrary
+    supported_modes: Vec<(u32, u32)>,
+}
+
+impl Default for DisplayModel {
+    fn default() -> Self {
+        Self {
+            display_size: None,
+            format: PixelFormat::default(),
+            framebuffer: Vec::new(),
+            damage: Vec::new(),
+            cursor: None,
+            cursor_position: None,
+            mouse_absolute: true,
+            scaling_mode: ScalingMode::default(),
+            rotation: Rotation::default(),
+            flip_horizontal: false,
+            flip_vertical: false,
+            zoom_level: 1.0,
+            resize_supported: true,
+            supported_modes: Vec::new(),
+        }
+    }
+}
+
+impl DisplayModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn display_size(&self) -> Option<(usize, usize)> {
+        self.display_size
+    }
+
+    // returns false if the size didn't change
+    pub fn set_display_size(&mut self, size: Option<(usize, usize)>) -> bool {
+        if self.display_size == size {
+            return false;
+        }
+
+        self.display_size = size;
+        self.damage.clear();
+        let len = size.map_or(0, |(w, h)| w * h * self.format.bytes_per_pixel());
+        if self.framebuffer.len() != len {
+            self.framebuffer = vec![0; len];
+        }
+        if let Some((w, h)) = size {
+            self.add_damage(0, 0, w as _, h as _);
+        }
+        true
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.framebuffer
+    }
+
+    // copy the area to the framebuffer, returns the updated area,
+    // to be damaged once uploaded
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_area(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        stride: i32,
+        format: PixelFormat,
+        data: &[u8],
+    ) -> Option<Rect> {
+        let (dw, dh) = self.display_size?;
+        let display = Rect::new(0, 0, dw as _, dh as _);
+        let area = Rect::new(x, y, w, h).intersect(&display)?;
+
+        let bpp = format.bytes_per_pixel();
+        if self.format != format {
+            // the guest switched format, start again from a blank framebuffer
+            self.format = format;
+            self.framebuffer = vec![0; dw * dh * bpp];
+        }
+
+        let (ox, oy) = ((area.x - x) as usize, (area.y - y) as usize);
+        let len = area.width as usize * bpp;
+        for row in 0..area.height as usize {
+            let src = (oy + row) * stride as usize + ox * bpp;
+            let dst = ((area.y as usize + row) * dw + area.x as usize) * bpp;
+            match (
+                data.get(src..src + len),
+                self.framebuffer.get_mut(dst..dst + len),
+            ) {
+                (Some(src), Some(dst)) => dst.copy_from_slice(src),
+                _ => {
+                    log::warn!("update_area: data is too short for {:?}", (x, y, w, h));
+                    break;
+                }
+            }
+        }
+
+        Some(area)
+    }
+
+    // returns false if the area is outside of the remote display
+    pub fn add_damage(&mut self, x: i32, y: i32, w: i32, h: i32) -> bool {
+        let Some((dw, dh)) = self.display_size else {
+            return false;
+        };
+        let display = Rect::new(0, 0, dw as _, dh as _);
+        match Rect::new(x, y, w, h).intersect(&display) {
+            Some(area) => {
+                self.damage.push(area);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn take_damage(&mut self) -> Vec<Rect> {
+        std::mem::take(&mut self.damage)
+    }
+
+    /// The framebuffer pixel, in premultiplied BGRA.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<[u8; 4]> {
+        let (dw, dh) = self.display_size?;
+        if x >= dw || y >= dh {
+            return None;
+        }
+        let bpp = self.format.bytes_per_pixel();
+        let offset = (y * dw + x) * bpp;
+        let mut pixel = [0; 4];
+        self.format
+            .to_bgra_premultiplied(self.framebuffer.get(offset..offset + bpp)?, &mut pixel);
+        Some(pixel)
+    }
+
+    /// The framebuffer, in premultiplied BGRA.
+    pub fn to_bgra_premultiplied(&self) -> Vec<u8> {
+        let (dw, dh) = self.display_size.unwrap_or((0, 0));
+        let mut pixels = vec![0; dw * dh * 4];
+        self.format
+            .to_bgra_premultiplied(&self.framebuffer, &mut pixels);
+        pixels
+    }
+
+    pub fn cursor(&self) -> Option<&CursorImage> {
+        self.cursor.as_ref()
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<CursorImage>) {
+        self.cursor = cursor;
+    }
+
+    pub fn cursor_position(&self) -> Option<(usize, usize)> {
+        self.cursor_position
+    }
+
+    pub fn set_cursor_position(&mut self, pos: Option<(usize, usize)>) {
+        self.cursor_position = pos;
+    }
+
+    pub fn mouse_absolute(&self) -> bool {
+        self.mouse_absolute
+    }
+
+    pub fn set_mouse_absolute(&mut self, absolute: bool) {
+        self.mouse_absolute = absolute;
+    }
+
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.scaling_mode
+    }
+
+    // the setters return false if the value didn't change
+    pub fn set_scaling_mode(&mut self, mode: ScalingMode) -> bool {
+        std::mem::replace(&mut self.scaling_mode, mode) != mode
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: Rotation) -> bool {
+        std::mem::replace(&mut self.rotation, rotation) != rotation
+    }
+
+    pub fn flip_horizontal(&self) -> bool {
+        self.flip_horizontal
+    }
+
+    pub fn set_flip_horizontal(&mut self, flip: bool) -> bool {
+        std::mem::replace(&mut self.flip_horizontal, flip) != flip
+    }
+
+    pub fn flip_vertical(&self) -> bool {
+        self.flip_vertical
+    }
+
+    pub fn set_flip_vertical(&mut self, flip: bool) -> bool {
+        std::mem::replace(&mut self.flip_vertical, flip) != flip
+    }
+
+    pub fn zoom_level(&self) -> f64 {
+        self.zoom_level
+    }
+
+    pub fn set_zoom_level(&mut self, level: f64) -> bool {
+        let level = level.clamp(ZOOM_MIN, ZOOM_MAX);
+        std::mem::replace(&mut self.zoom_level, level) != level
+    }
+
+    pub fn resize_supported(&self) -> bool {
+        self.resize_supported
+    }
+
+    pub fn set_resize_supported(&mut self, supported: bool) -> bool {
+        std::mem::replace(&mut self.resize_supported, supported) != supported
+    }
+
+    pub fn supported_modes(&self) -> &[(u32, u32)] {
+        &self.supported_modes
+    }
+
+    pub fn set_supported_modes(&mut self, modes: &[(u32, u32)]) -> bool {
+        if self.supported_modes == modes {
+            return false;
+        }
+        self.supported_modes = modes.to_vec();
+        true
+    }
+
+    // the size to request from the remote for the given size, if any
+    pub fn snap_size(&self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if !self.resize_supported {
+            return None;
+        }
+        if self.supported_modes.is_empty() {
+            return Some((width, height));
+        }
+        // the largest mode that fits, or else the closest
+        let fits = self
+            .supported_modes
+            .iter()
+            .filter(|(w, h)| *w <= width && *h <= height)
+            .max_by_key(|(w, h)| *w as u64 * *h as u64);
+        let closest = || {
+            self.supported_modes.iter().min_by_key(|(w, h)| {
+                let dw = *w as i64 - width as i64;
+                let dh = *h as i64 - height as i64;
+                dw * dw + dh * dh
+            })
+        };
+        fits.or_else(closest).copied()
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        matches!(self.rotation, Rotation::Rotate90 | Rotation::Rotate270)
+    }
+
+    // the remote display size, once rotated
+    pub fn oriented_size(&self) -> Option<(u32, u32)> {
+        let (w, h) = self.display_size?;
+        if self.is_rotated() {
+            Some((h as _, w as _))
+        } else {
+            Some((w as _, h as _))
+        }
+    }
+
+    // remote display pos -> rotated & flipped pos
+    pub fn orient(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let (w, h) = self.display_size?;
+        let (w, h) = (w as f64, h as f64);
+        let (x, y) = match self.rotation {
+            Rotation::Rotate90 => (h - y, x),
+            Rotation::Rotate180 => (w - x, h - y),
+            Rotation::Rotate270 => (y, w - x),
+            _ => (x, y),
+        };
+        let (ow, oh) = self.oriented_size()?;
+        let x = if self.flip_horizontal {
+            ow as f64 - x
+        } else {
+            x
+        };
+        let y = if self.flip_vertical { oh as f64 - y } else { y };
+        Some((x, y))
+    }
+
+    // rotated & flipped pos -> remote display pos
+    pub fn unorient(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let (ow, oh) = self.oriented_size()?;
+        let (ow, oh) = (ow as f64, oh as f64);
+        let x = if self.flip_horizontal { ow - x } else { x };
+        let y = if self.flip_vertical { oh - y } else { y };
+        Some(match self.rotation {
+            Rotation::Rotate90 => (y, ow - x),
+            Rotation::Rotate180 => (ow - x, oh - y),
+            Rotation::Rotate270 => (oh - y, x),
+            _ => (x, y),
+        })
+    }
+
+    // widget relative motion -> remote display relative motion
+    pub fn unorient_delta(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let dx = if self.flip_horizontal { -dx } else { dx };
+        let dy = if self.flip_vertical { -dy } else { dy };
+        match self.rotation {
+            Rotation::Rotate90 => (dy, -dx),
+            Rotation::Rotate180 => (-dx, -dy),
+            Rotation::Rotate270 => (-dy, dx),
+            _ => (dx, dy),
+        }
+    }
+
+    // remote display -> widget (in device pixels) scaling factors
+    pub fn display_scale(&self, geometry: &Geometry) -> Option<(f64, f64)> {
+        let (dw, dh) = self.oriented_size()?;
+        let sf = geometry.scale_factor;
+        let (w, h) = (geometry.width * sf, geometry.height * sf);
+        let (sw, sh) = (w as f64 / dw as f64, h as f64 / dh as f64);
+
+        let zoom = self.zoom_level;
+        let scale = match self.scaling_mode {
+            ScalingMode::Fill => sw.max(sh),
+            ScalingMode::Original => 1.0,
+            ScalingMode::IntegerScale => {
+                let scale = sw.min(sh);
+                if scale >= 1.0 {
+                    scale.floor()
+                } else {
+                    scale
+                }
+            }
+            ScalingMode::Stretch => return Some((sw * zoom, sh * zoom)),
+            _ => sw.min(sh),
+        };
+        Some((scale * zoom, scale * zoom))
+    }
+
+    // the remote display area, in widget device pixels (may exceed the widget)
+    pub fn viewport(&self, geometry: &Geometry) -> Option<Rect> {
+        let (dw, dh) = self.oriented_size()?;
+        let (sw, sh) = self.display_scale(geometry)?;
+
+        let sf = geometry.scale_factor;
+        let (w, h) = (geometry.width * sf, geometry.height * sf);
+        let (vw, vh) = (
+            (dw as f64 * sw).round() as i32,
+            (dh as f64 * sh).round() as i32,
+        );
+        if vw <= 0 || vh <= 0 {
+            return None;
+        }
+        // centered, or scrolled when larger than the widget
+        let origin = |size: i32, vsize: i32, scroll: Option<f64>| match scroll {
+            Some(value) if vsize > size => -(value * sf as f64).round() as i32,
+            _ => (size - vsize) / 2,
+        };
+        Some(Rect::new(
+            origin(w, vw, geometry.scroll.0),
+            origin(h, vh, geometry.scroll.1),
+            vw,
+            vh,
+        ))
+    }
+
+    // the remote display size, in widget logical pixels
+    pub fn content_size(&self, geometry: &Geometry) -> Option<(f64, f64)> {
+        let (dw, dh) = self.oriented_size()?;
+        let (sw, sh) = self.display_scale(geometry)?;
+        let sf = geometry.scale_factor as f64;
+        Some((dw as f64 * sw / sf, dh as f64 * sh / sf))
+    }
+
+    // widget -> remote display pos
+    pub fn transform_pos(&self, geometry: &Geometry, x: f64, y: f64) -> Option<(f64, f64)> {
+        let sf = geometry.scale_factor as f64;
+        let vp = self.viewport(geometry)?;
+        if !vp.contains_point((x * sf) as _, (y * sf) as _) {
+            return None;
+        }
+        self.transform_pos_unclamped(geometry, x, y)
+    }
+
+    // widget -> remote display pos, even outside of the remote display
+    pub fn transform_pos_unclamped(
+        &self,
+        geometry: &Geometry,
+        x: f64,
+        y: f64,
+    ) -> Option<(f64, f64)> {
+        let sf = geometry.scale_factor as f64;
+        let vp = self.viewport(geometry)?;
+        let (x, y) = (x * sf, y * sf);
+        let (sw, sh) = self.oriented_size()?;
+        let x = (x - vp.x as f64) * (sw as f64 / vp.width as f64);
+        let y = (y - vp.y as f64) * (sh as f64 / vp.height as f64);
+        self.unorient(x, y)
+    }
+
+    // remote display pos -> widget pos
+    pub fn transform_pos_inv(&self, geometry: &Geometry, x: f64, y: f64) -> Option<(f64, f64)> {
+        let sf = geometry.scale_factor as f64;
+        let (x, y) = self.orient(x, y)?;
+        let vp = self.viewport(geometry)?;
+        let (sw, sh) = self.oriented_size()?;
+        let x = x * (vp.width as f64 / sw as f64) + vp.x as f64;
+        let y = y * (vp.height as f64 / sh as f64) + vp.y as f64;
+        Some((x / sf, y / sf))
+    }
+
+    /// The motion event for a pointer at the widget position, if over the remote display.
+    pub fn pointer_motion(&self, geometry: &Geometry, x: f64, y: f64) -> Option<InputEvent> {
+        self.transform_pos(geometry, x, y)
+            .map(|(x, y)| InputEvent::Motion { x, y })
+    }
+
+    /// The relative motion event for a widget relative motion, in logical pixels.
+    pub fn relative_motion(&self, dx: f64, dy: f64) -> InputEvent {
+        let (dx, dy) = self.unorient_delta(dx, dy);
+        InputEvent::MotionRelative { dx, dy }
+    }
+
+    /// The discrete scroll events for a scroll delta.
+    pub fn scroll_discrete(&self, dx: f64, dy: f64) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        if dy >= 1.0 {
+            events.push(InputEvent::ScrollDiscrete(Scroll::Down));
+        } else if dy <= -1.0 {
+            events.push(InputEvent::ScrollDiscrete(Scroll::Up));
+        }
+        if dx >= 1.0 {
+            events.push(InputEvent::ScrollDiscrete(Scroll::Right));
+        } else if dx <= -1.0 {
+            events.push(InputEvent::ScrollDiscrete(Scroll::Left));
+        }
+        events
+    }
+
+    /// The smooth scroll event for a scroll delta.
+    ///
+    /// The deltas are in wheel clicks for a wheel, also given in 120ths of a click,
+    /// or in surface units for touchpads, where the 120ths are 0.
+    pub fn scroll_smooth(&self, dx: f64, dy: f64, wheel: bool) -> InputEvent {
+        let v120 = |d: f64| if wheel { (d * 120.0).round() as i32 } else { 0 };
+        InputEvent::ScrollSmooth {
+            dx,
+            dy,
+            v120_x: v120(dx),
+            v120_y: v120(dy),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(width: usize, height: usize) -> DisplayModel {
+        let mut model = DisplayModel::new();
+        model.set_display_size(Some((width, height)));
+        model.take_damage();
+        model
+    }
+
+    fn geometry(width: i32, height: i32) -> Geometry {
+        Geometry {
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    fn discrete(events: &[InputEvent]) -> Vec<Scroll> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                InputEvent::ScrollDiscrete(s) => Some(*s),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn transform_pos_centered() {
+        let model = model(100, 50);
+        let geometry = geometry(200, 50);
+        assert_eq!(model.transform_pos(&geometry, 10.0, 10.0), None);
+        assert_eq!(
+            model.transform_pos(&geometry, 60.0, 10.0),
+            Some((10.0, 10.0))
+        );
+        assert_eq!(
+            model.transform_pos_inv(&geometry, 10.0, 10.0),
+            Some((60.0, 10.0))
+        );
+    }
+
+    #[test]
+    fn transform_pos_rotated() {
+        let mut model = model(100, 50);
+        model.set_rotation(Rotation::Rotate90);
+        assert_eq!(model.oriented_size(), Some((50, 100)));
+
+        let geometry = geometry(50, 100);
+        assert_eq!(
+            model.transform_pos(&geometry, 10.0, 20.0),
+            Some((20.0, 40.0))
+        );
+        assert_eq!(
+            model.transform_pos_inv(&geometry, 20.0, 40.0),
+            Some((10.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn transform_pos_flipped() {
+        let mut model = model(100, 50);
+        let geometry = geometry(100, 50);
+        model.set_flip_horizontal(true);
+        assert_eq!(
+            model.transform_pos(&geometry, 10.0, 20.0),
+            Some((90.0, 20.0))
+        );
+
+        model.set_flip_horizontal(false);
+        model.set_flip_vertical(true);
+        model.set_rotation(Rotation::Rotate180);
+        assert_eq!(
+            model.transform_pos(&geometry, 10.0, 20.0),
+            Some((90.0, 20.0))
+        );
+        assert_eq!(
+            model.transform_pos_inv(&geometry, 90.0, 20.0),
+            Some((10.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn snap_size() {
+        let mut model = DisplayModel::new();
+        assert_eq!(model.snap_size(1280, 800), Some((1280, 800)));
+
+        model.set_supported_modes(&[(640, 480), (1024, 768), (1920, 1080)]);
+        // the largest that fits
+        assert_eq!(model.snap_size(1280, 800), Some((1024, 768)));
+        // none fits, the closest
+        assert_eq!(model.snap_size(600, 400), Some((640, 480)));
+
+        model.set_resize_supported(false);
+        assert_eq!(model.snap_size(1280, 800), None);
+    }
+
+    #[test]
+    fn scroll_discrete() {
+        let model = DisplayModel::new();
+        assert_eq!(discrete(&model.scroll_discrete(0.0, 1.0)), [Scroll::Down]);
+        assert_eq!(
+            discrete(&model.scroll_discrete(-1.0, -1.0)),
+            [Scroll::Up, Scroll::Left]
+        );
+        assert!(model.scroll_discrete(0.5, 0.0).is_empty());
+    }
+
+    #[test]
+    fn scroll_smooth() {
+        let model = DisplayModel::new();
+        assert_eq!(
+            model.scroll_smooth(0.0, 0.5, true),
+            InputEvent::ScrollSmooth {
+                dx: 0.0,
+                dy: 0.5,
+                v120_x: 0,
+                v120_y: 60
+            }
+        );
+        assert_eq!(
+            model.scroll_smooth(-2.0, 0.004, true),
+            InputEvent::ScrollSmooth {
+                dx: -2.0,
+                dy: 0.004,
+                v120_x: -240,
+                v120_y: 0
+            }
+        );
+        // touchpads don't scroll in clicks
+        assert_eq!(
+            model.scroll_smooth(0.0, 12.0, false),
+            InputEvent::ScrollSmooth {
+                dx: 0.0,
+                dy: 12.0,
+                v120_x: 0,
+                v120_y: 0
+            }
+        );
+    }
+
+    #[test]
+    fn update_area_damage() {
+        let mut model = DisplayModel::new();
+        model.set_display_size(Some((4, 4)));
+        assert_eq!(model.take_damage(), [Rect::new(0, 0, 4, 4)]);
+        assert!(model.take_damage().is_empty());
+
+        // clipped to the display
+        let data = [1, 2, 3, 0].repeat(16);
+        let area = model.update_area(2, 2, 4, 4, 16, PixelFormat::Xrgb8888, &data);
+        assert_eq!(area, Some(Rect::new(2, 2, 2, 2)));
+        // damaged by the display, once uploaded
+        assert!(model.take_damage().is_empty());
+        assert_eq!(model.pixel(3, 3), Some([1, 2, 3, 0xff]));
+        assert_eq!(model.pixel(1, 1), Some([0, 0, 0, 0xff]));
+
+        assert_eq!(
+            model.update_area(4, 0, 2, 2, 8, PixelFormat::Xrgb8888, &data),
+            None
+        );
+
+        assert!(model.add_damage(3, 3, 2, 2));
+        assert!(!model.add_damage(4, 0, 2, 2));
+        assert_eq!(model.take_damage(), [Rect::new(3, 3, 1, 1)]);
+
+        // a format change starts from a blank framebuffer
+        let data = [0; 8];
+        model.update_area(0, 0, 1, 1, 8, PixelFormat::Rgb565, &data);
+        assert_eq!(model.format(), PixelFormat::Rgb565);
+        assert!(model.framebuffer().iter().all(|b| *b == 0));
+    }
+}
@@ -0,0 +1,379 @@
+use gtk::{glib, prelude::*};
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::{Display, DisplayExt, Rect};
+
+/// A monitor configuration entry, as sent to the remote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitorLayout {
+    // the monitor index
+    pub nth: usize,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub width_mm: u32,
+    pub height_mm: u32,
+    pub primary: bool,
+    // the scale of the client monitor, possibly fractional
+    pub scale: f64,
+}
+
+impl Default for MonitorLayout {
+    fn default() -> Self {
+        Self {
+            nth: 0,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            width_mm: 0,
+            height_mm: 0,
+            primary: false,
+            scale: 1.0,
+        }
+    }
+}
+
+type DisplayFactory = Box<dyn Fn(usize) -> Display>;
+type DisplayCallback = Rc<dyn Fn(usize, &Display)>;
+type LayoutCallback = Rc<dyn Fn(&[MonitorLayout])>;
+
+#[derive(Debug)]
+struct Monitor {
+    display: Display,
+    resize_id: Option<glib::SignalHandlerId>,
+    // the area of the guest framebuffer shown by the display
+    head: Option<Rect>,
+    // the last resize-request: width, height, width_mm, height_mm, scale
+    request: Option<(u32, u32, u32, u32, f64)>,
+}
+
+#[derive(Default)]
+struct MonitorManagerInner {
+    monitors: RefCell<Vec<Monitor>>,
+    factory: RefCell<Option<DisplayFactory>>,
+    added: RefCell<Vec<DisplayCallback>>,
+    removed: RefCell<Vec<DisplayCallback>>,
+    layout: RefCell<Vec<LayoutCallback>>,
+    layout_id: RefCell<Option<glib::SourceId>>,
+}
+
+impl MonitorManagerInner {
+    fn layout(&self) -> Vec<MonitorLayout> {
+        let mut x = 0;
+        let mut layout = Vec::new();
+        // side by side, in monitor order
+        for (nth, m) in self.monitors.borrow().iter().enumerate() {
+            let size = m
+                .request
+                .or_else(|| m.head.map(|r| (r.width as _, r.height as _, 0, 0, 1.0)))
+                .or_else(|| {
+                    m.display
+                        .display_size()
+                        .map(|(w, h)| (w as _, h as _, 0, 0, 1.0))
+                });
+            let Some((width, height, width_mm, height_mm, scale)) = size else {
+                continue;
+            };
+            layout.push(MonitorLayout {
+                nth,
+                x,
+                y: 0,
+                width,
+                height,
+                width_mm,
+                height_mm,
+                primary: nth == 0,
+                scale,
+            });
+            x += width as i32;
+        }
+        layout
+    }
+
+    fn resize_requested(self: &Rc<Self>, display: &Display, request: (u32, u32, u32, u32, f64)) {
+        if let Some(m) = self
+            .monitors
+            .borrow_mut()
+            .iter_mut()
+            .find(|m| &m.display == display)
+        {
+            m.request = Some(request);
+        }
+
+        // several widgets are usually resized at once, send a single layout
+        if self.layout_id.borrow().is_some() {
+            return;
+        }
+        let weak = Rc::downgrade(self);
+        let id = glib::idle_add_local_once(move || {
+            if let Some(inner) = weak.upgrade() {
+                inner.layout_id.replace(None);
+                inner.emit_layout();
+            }
+        });
+        self.layout_id.replace(Some(id));
+    }
+
+    fn emit_layout(&self) {
+        let layout = self.layout();
+        if layout.is_empty() {
+            return;
+        }
+        log::debug!("monitor-layout: {:?}", layout);
+        let callbacks = self.layout.borrow().clone();
+        for cb in callbacks {
+            cb(&layout);
+        }
+    }
+
+    fn watch(self: &Rc<Self>, display: &Display) -> glib::SignalHandlerId {
+        let weak: Weak<Self> = Rc::downgrade(self);
+        display.connect_resize_request(move |display, width, height, wmm, hmm, scale| {
+            if let Some(inner) = weak.upgrade() {
+                inner.resize_requested(display, (width, height, wmm, hmm, scale));
+            }
+        })
+    }
+
+    fn notify(callbacks: &RefCell<Vec<DisplayCallback>>, nth: usize, display: &Display) {
+        let callbacks = callbacks.borrow().clone();
+        for cb in callbacks {
+            cb(nth, display);
+        }
+    }
+}
+
+impl Drop for MonitorManagerInner {
+    fn drop(&mut self) {
+        for m in self.monitors.get_mut().drain(..) {
+            if let Some(id) = m.resize_id {
+                m.display.disconnect(id);
+            }
+        }
+        if let Some(id) = self.layout_id.get_mut().take() {
+            id.remove();
+        }
+    }
+}
+
+/// Drives one `Display` per remote head of a session.
+///
+/// The first display is the primary monitor. Additional displays are spawned
+/// with the display factory when the remote reports more heads, and the
+/// `resize-request` of all displays are aggregated into a single layout.
+#[derive(Clone, Default)]
+pub struct MonitorManager {
+    inner: Rc<MonitorManagerInner>,
+}
+
+impl std::fmt::Debug for MonitorManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MonitorManager")
+            .field("monitors", &self.inner.monitors)
+            .finish()
+    }
+}
+
+impl MonitorManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The manager of the monitors of `display`'s session, `display` being the primary one.
+    ///
+    /// `monitors` holds the display reference to its manager: the existing manager is
+    /// returned, or a new one is stored there. The manager isn't kept alive by the display.
+    /// `send_layout` is called with the aggregated layout.
+    pub fn for_display<D, F>(
+        display: &D,
+        monitors: &RefCell<WeakMonitorManager>,
+        send_layout: F,
+    ) -> Self
+    where
+        D: IsA<Display>,
+        F: Fn(&D, &[MonitorLayout]) + 'static,
+    {
+        if let Some(manager) = monitors.borrow().upgrade() {
+            return manager;
+        }
+
+        let manager = Self::new();
+        manager.add_display(display);
+        let weak = display.downgrade();
+        manager.connect_layout(move |layout| {
+            if let Some(display) = weak.upgrade() {
+                send_layout(&display, layout);
+            }
+        });
+        monitors.replace(manager.downgrade());
+        manager
+    }
+
+    pub fn downgrade(&self) -> WeakMonitorManager {
+        WeakMonitorManager(Rc::downgrade(&self.inner))
+    }
+
+    /// Set the function creating the display of the nth monitor.
+    pub fn set_display_factory<F: Fn(usize) -> Display + 'static>(&self, f: F) {
+        self.inner.factory.replace(Some(Box::new(f)));
+    }
+
+    /// Manage an existing display, as the next monitor.
+    pub fn add_display(&self, display: &impl IsA<Display>) -> usize {
+        let display = display.upcast_ref::<Display>().clone();
+        let nth = {
+            let mut monitors = self.inner.monitors.borrow_mut();
+            monitors.push(Monitor {
+                resize_id: Some(self.inner.watch(&display)),
+                display: display.clone(),
+                head: None,
+                request: None,
+            });
+            monitors.len() - 1
+        };
+        MonitorManagerInner::notify(&self.inner.added, nth, &display);
+        nth
+    }
+
+    /// Update the remote heads, in guest framebuffer coordinates.
+    ///
+    /// Displays are spawned or removed to match the number of heads. The
+    /// primary display is always kept.
+    pub fn set_heads(&self, heads: &[Rect]) {
+        let inner = &self.inner;
+        // add_display() borrows the monitors mutably
+        let n_monitors = inner.monitors.borrow().len();
+        for nth in n_monitors..heads.len() {
+            let display = match &*inner.factory.borrow() {
+                Some(factory) => factory(nth),
+                None => {
+                    log::warn!("No display factory, ignoring monitor {}", nth);
+                    break;
+                }
+            };
+            self.add_display(&display);
+        }
+
+        let removed: Vec<_> = {
+            let mut monitors = inner.monitors.borrow_mut();
+            for (m, head) in monitors.iter_mut().zip(heads.iter()) {
+                m.head = Some(*head);
+            }
+            let keep = heads.len().max(1).min(monitors.len());
+            if heads.is_empty() {
+                if let Some(m) = monitors.first_mut() {
+                    m.head = None;
+                }
+            }
+            monitors.drain(keep..).collect()
+        };
+        for (i, m) in removed.into_iter().enumerate() {
+            if let Some(id) = m.resize_id {
+                m.display.disconnect(id);
+            }
+            MonitorManagerInner::notify(&inner.removed, heads.len().max(1) + i, &m.display);
+        }
+    }
+
+    pub fn n_monitors(&self) -> usize {
+        self.inner.monitors.borrow().len()
+    }
+
+    pub fn display(&self, nth: usize) -> Option<Display> {
+        self.inner
+            .monitors
+            .borrow()
+            .get(nth)
+            .map(|m| m.display.clone())
+    }
+
+    pub fn displays(&self) -> Vec<Display> {
+        self.inner
+            .monitors
+            .borrow()
+            .iter()
+            .map(|m| m.display.clone())
+            .collect()
+    }
+
+    /// The area of the guest framebuffer shown by the nth monitor.
+    pub fn head(&self, nth: usize) -> Option<Rect> {
+        self.inner.monitors.borrow().get(nth).and_then(|m| m.head)
+    }
+
+    pub fn nth_monitor(&self, display: &impl IsA<Display>) -> Option<usize> {
+        let display = display.upcast_ref::<Display>();
+        self.inner
+            .monitors
+            .borrow()
+            .iter()
+            .position(|m| &m.display == display)
+    }
+
+    /// The monitor layout requested by the displays.
+    pub fn layout(&self) -> Vec<MonitorLayout> {
+        self.inner.layout()
+    }
+
+    pub fn connect_display_added<F: Fn(usize, &Display) + 'static>(&self, f: F) {
+        self.inner.added.borrow_mut().push(Rc::new(f));
+    }
+
+    pub fn connect_display_removed<F: Fn(usize, &Display) + 'static>(&self, f: F) {
+        self.inner.removed.borrow_mut().push(Rc::new(f));
+    }
+
+    /// Called with the aggregated layout, whenever a display requests a new size.
+    pub fn connect_layout<F: Fn(&[MonitorLayout]) + 'static>(&self, f: F) {
+        self.inner.layout.borrow_mut().push(Rc::new(f));
+    }
+}
+
+/// A weak reference to a `MonitorManager`, for the displays it manages.
+#[derive(Clone, Default)]
+pub struct WeakMonitorManager(Weak<MonitorManagerInner>);
+
+impl std::fmt::Debug for WeakMonitorManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("WeakMonitorManager").finish()
+    }
+}
+
+impl WeakMonitorManager {
+    pub fn upgrade(&self) -> Option<MonitorManager> {
+        self.0.upgrade().map(|inner| MonitorManager { inner })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_heads_spawns_displays() {
+        // the displays are widgets, skip without a display server
+        if gtk::init().is_err() {
+            return;
+        }
+        let manager = MonitorManager::new();
+        manager.add_display(&glib::Object::new::<Display>(&[]));
+        manager.set_display_factory(|_| glib::Object::new::<Display>(&[]));
+
+        let heads = [Rect::new(0, 0, 640, 480), Rect::new(640, 0, 800, 600)];
+        manager.set_heads(&heads);
+        assert_eq!(manager.n_monitors(), 2);
+        assert_eq!(manager.head(1), Some(heads[1]));
+        let display = manager.display(1).unwrap();
+        assert_eq!(manager.nth_monitor(&display), Some(1));
+
+        // the primary display is kept
+        manager.set_heads(&[]);
+        assert_eq!(manager.n_monitors(), 1);
+        assert_eq!(manager.head(0), None);
+    }
+}
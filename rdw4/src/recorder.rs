@@ -0,0 +1,439 @@
+use futures::{channel::oneshot, future};
+use gst::prelude::*;
+use gtk::{cairo, gdk, glib, prelude::*};
+use std::{
+    cell::{Cell, RefCell},
+    error::Error,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{Display, DisplayExt};
+
+// the gdk::Texture::download() memory layout
+#[cfg(target_endian = "little")]
+const VIDEO_FORMAT: &str = "BGRx";
+#[cfg(target_endian = "big")]
+const VIDEO_FORMAT: &str = "xRGB";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecorderFormat {
+    WebM,
+    Mp4,
+}
+
+impl RecorderFormat {
+    fn video_encoder(self) -> &'static str {
+        match self {
+            Self::WebM => "vp8enc deadline=1 cpu-used=4",
+            Self::Mp4 => "x264enc tune=zerolatency speed-preset=veryfast ! h264parse",
+        }
+    }
+
+    fn audio_encoder(self) -> &'static str {
+        match self {
+            Self::WebM => "opusenc",
+            Self::Mp4 => "avenc_aac ! aacparse",
+        }
+    }
+
+    fn muxer(self) -> &'static str {
+        match self {
+            Self::WebM => "webmmux",
+            Self::Mp4 => "mp4mux",
+        }
+    }
+}
+
+/// A handle to feed a recording with audio, see `GstAudio::set_recorder_out()`.
+#[derive(Debug, Clone, Default)]
+pub struct RecorderAudio(Arc<Mutex<Option<gst_app::AppSrc>>>);
+
+impl RecorderAudio {
+    fn set(&self, src: Option<gst_app::AppSrc>) {
+        *self.0.lock().unwrap() = src;
+    }
+
+    pub fn push(&self, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        if let Some(src) = &*self.0.lock().unwrap() {
+            src.push_buffer(gst::Buffer::from_slice(data.to_vec()))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct RecorderInner {
+    display: glib::WeakRef<Display>,
+    path: PathBuf,
+    format: RecorderFormat,
+    max_fps: Cell<u32>,
+    audio_caps: RefCell<Option<String>>,
+    audio: RecorderAudio,
+    pipeline: RefCell<Option<gst::Pipeline>>,
+    video_src: RefCell<Option<gst_app::AppSrc>>,
+    audio_src: RefCell<Option<gst_app::AppSrc>>,
+    video_size: Cell<Option<(i32, i32)>>,
+    // the last frame pushed, updated with the damaged areas
+    frame: RefCell<Vec<u8>>,
+    damage: RefCell<Vec<gdk::Rectangle>>,
+    paused: Cell<bool>,
+    frame_damage_id: RefCell<Option<glib::SignalHandlerId>>,
+    frame_timeout_id: RefCell<Option<glib::SourceId>>,
+    last_frame: Cell<Option<Instant>>,
+}
+
+impl RecorderInner {
+    fn description(&self, width: i32, height: i32) -> String {
+        let mut desc = format!(
+            "appsrc name=videosrc is-live=1 do-timestamp=1 format=time ! queue ! videoconvert ! videoscale add-borders=1 ! video/x-raw,width={},height={} ! {} ! queue ! {} name=mux ! filesink name=sink",
+            width,
+            height,
+            self.format.video_encoder(),
+            self.format.muxer()
+        );
+        if let Some(caps) = &*self.audio_caps.borrow() {
+            desc.push_str(&format!(
+                " appsrc name=audiosrc is-live=1 do-timestamp=1 format=time caps=\"{}\" ! queue ! audioconvert ! audioresample ! {} ! queue ! mux.",
+                caps,
+                self.format.audio_encoder()
+            ));
+        }
+        desc
+    }
+
+    fn frame_damaged(self: &Rc<Self>, region: &cairo::Region) {
+        if self.paused.get() || region.is_empty() {
+            return;
+        }
+        self.damage
+            .borrow_mut()
+            .extend((0..region.num_rectangles()).map(|i| {
+                let r = region.rectangle(i);
+                gdk::Rectangle::new(r.x(), r.y(), r.width(), r.height())
+            }));
+        if self.frame_timeout_id.borrow().is_some() {
+            return;
+        }
+
+        let max_fps = self.max_fps.get();
+        let delay = match (max_fps > 0, self.last_frame.get()) {
+            (true, Some(last)) => (Duration::from_secs(1) / max_fps).saturating_sub(last.elapsed()),
+            _ => Duration::ZERO,
+        };
+        // read the latest content back later, not from the display rendering
+        let weak = Rc::downgrade(self);
+        let id = glib::timeout_add_local_once(delay, move || {
+            if let Some(inner) = weak.upgrade() {
+                inner.frame_timeout_id.replace(None);
+                inner.push_frame();
+            }
+        });
+        self.frame_timeout_id.replace(Some(id));
+    }
+
+    fn push_frame(&self) {
+        if self.paused.get() {
+            return;
+        }
+        let (display, src) = match (self.display.upgrade(), self.video_src.borrow().clone()) {
+            (Some(display), Some(src)) => (display, src),
+            _ => return,
+        };
+        let damage = self.damage.take();
+        let mut frame = self.frame.borrow_mut();
+        if frame.is_empty() || !read_damage(&display, self.video_size.get(), &damage, &mut frame) {
+            // read the whole content back
+            let texture = match display.snapshot_texture() {
+                Some(texture) => texture,
+                None => return,
+            };
+            let (width, height) = (texture.width(), texture.height());
+            if self.video_size.replace(Some((width, height))) != Some((width, height)) {
+                src.set_caps(Some(
+                    &gst::Caps::builder("video/x-raw")
+                        .field("format", VIDEO_FORMAT)
+                        .field("width", width)
+                        .field("height", height)
+                        .field("framerate", gst::Fraction::new(0, 1))
+                        .build(),
+                ));
+            }
+            let stride = width as usize * 4;
+            frame.resize(stride * height as usize, 0);
+            texture.download(&mut frame, stride);
+        }
+        self.last_frame.set(Some(Instant::now()));
+
+        if let Err(e) = src.push_buffer(gst::Buffer::from_slice(frame.clone())) {
+            log::warn!("Failed to push video frame: {}", e);
+        }
+    }
+
+    fn disconnect(&self) {
+        if let (Some(display), Some(id)) = (self.display.upgrade(), self.frame_damage_id.take()) {
+            display.disconnect(id);
+        }
+        if let Some(id) = self.frame_timeout_id.take() {
+            id.remove();
+        }
+        self.audio.set(None);
+    }
+}
+
+// copy the damaged areas to the last frame, false if it must be read whole
+#[cfg(not(feature = "bindings"))]
+fn read_damage(
+    display: &Display,
+    size: Option<(i32, i32)>,
+    damage: &[gdk::Rectangle],
+    frame: &mut [u8],
+) -> bool {
+    use glib::subclass::prelude::*;
+
+    let imp = crate::display::imp::Display::from_obj(display);
+    let (width, height) = match (size, imp.content_size()) {
+        (Some(size), Some((w, h))) if size == (w as i32, h as i32) => size,
+        _ => return false,
+    };
+    let bounds = gdk::Rectangle::new(0, 0, width, height);
+    damage.iter().all(|r| match r.intersect(&bounds) {
+        Some(r) => imp.read_area(&r, frame, width as usize * 4),
+        None => true,
+    })
+}
+
+#[cfg(feature = "bindings")]
+fn read_damage(
+    _display: &Display,
+    _size: Option<(i32, i32)>,
+    _damage: &[gdk::Rectangle],
+    _frame: &mut [u8],
+) -> bool {
+    false
+}
+
+impl Drop for RecorderInner {
+    fn drop(&mut self) {
+        self.disconnect();
+        if let Some(pipeline) = self.pipeline.take() {
+            log::warn!("Recorder dropped while recording, the recording may be truncated");
+            let _ = pipeline.set_state(gst::State::Null);
+        }
+    }
+}
+
+/// Records the frames of a `Display`, along with some playback audio, to a file.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    inner: Rc<RecorderInner>,
+}
+
+impl Recorder {
+    pub fn new(
+        display: &impl IsA<Display>,
+        path: impl AsRef<Path>,
+        format: RecorderFormat,
+    ) -> Result<Self, Box<dyn Error>> {
+        gst::init()?;
+
+        let inner = RecorderInner {
+            display: display.upcast_ref::<Display>().downgrade(),
+            path: path.as_ref().to_owned(),
+            format,
+            max_fps: Cell::new(30),
+            audio_caps: Default::default(),
+            audio: Default::default(),
+            pipeline: Default::default(),
+            video_src: Default::default(),
+            audio_src: Default::default(),
+            video_size: Default::default(),
+            frame: Default::default(),
+            damage: Default::default(),
+            paused: Default::default(),
+            frame_damage_id: Default::default(),
+            frame_timeout_id: Default::default(),
+            last_frame: Default::default(),
+        };
+        Ok(Self {
+            inner: Rc::new(inner),
+        })
+    }
+
+    /// Limit the recording frame rate, 0 to record every frame.
+    pub fn set_max_fps(&self, max_fps: u32) {
+        self.inner.max_fps.set(max_fps);
+    }
+
+    /// Add an audio track, must be called before `start()`.
+    pub fn add_audio(&self, caps: &str) -> Result<RecorderAudio, Box<dyn Error>> {
+        if self.inner.pipeline.borrow().is_some() {
+            return Err("Recording already started".into());
+        }
+        self.inner.audio_caps.replace(Some(caps.to_string()));
+        Ok(self.inner.audio.clone())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.inner.pipeline.borrow().is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.get()
+    }
+
+    pub fn start(&self) -> Result<(), Box<dyn Error>> {
+        let inner = &self.inner;
+        if inner.pipeline.borrow().is_some() {
+            return Err("Recording already started".into());
+        }
+
+        let display = inner.display.upgrade().ok_or("Display is gone")?;
+        let (width, height) = display.display_size().ok_or("Unknown display size")?;
+        // most encoders want even dimensions
+        let (width, height) = ((width & !1) as i32, (height & !1) as i32);
+
+        let pipeline = gst::parse_launch(&inner.description(width, height))?;
+        let pipeline = pipeline.dynamic_cast::<gst::Pipeline>().unwrap();
+        pipeline
+            .by_name("sink")
+            .unwrap()
+            .set_property("location", inner.path.to_string_lossy().as_ref());
+        let video_src = pipeline
+            .by_name("videosrc")
+            .unwrap()
+            .dynamic_cast::<gst_app::AppSrc>()
+            .unwrap();
+        let audio_src = pipeline
+            .by_name("audiosrc")
+            .map(|src| src.dynamic_cast::<gst_app::AppSrc>().unwrap());
+        pipeline.set_state(gst::State::Playing)?;
+
+        inner.video_src.replace(Some(video_src));
+        inner.audio_src.replace(audio_src.clone());
+        inner.audio.set(audio_src);
+        inner.pipeline.replace(Some(pipeline));
+        inner.paused.set(false);
+        inner.frame.borrow_mut().clear();
+
+        let weak = Rc::downgrade(inner);
+        let id = display.connect_frame_damage(move |_, region| {
+            if let Some(inner) = weak.upgrade() {
+                inner.frame_damaged(region);
+            }
+        });
+        inner.frame_damage_id.replace(Some(id));
+        inner.push_frame();
+        Ok(())
+    }
+
+    pub fn pause(&self) -> Result<(), Box<dyn Error>> {
+        let inner = &self.inner;
+        let pipeline = inner
+            .pipeline
+            .borrow()
+            .clone()
+            .ok_or("Recording not started")?;
+        if inner.paused.replace(true) {
+            return Ok(());
+        }
+        inner.audio.set(None);
+        // the paused duration is excluded from the running time
+        pipeline.set_state(gst::State::Paused)?;
+        Ok(())
+    }
+
+    pub fn resume(&self) -> Result<(), Box<dyn Error>> {
+        let inner = &self.inner;
+        let pipeline = inner
+            .pipeline
+            .borrow()
+            .clone()
+            .ok_or("Recording not started")?;
+        if !inner.paused.replace(false) {
+            return Ok(());
+        }
+        pipeline.set_state(gst::State::Playing)?;
+        inner.audio.set(inner.audio_src.borrow().clone());
+        // the damage while paused was not tracked
+        inner.frame.borrow_mut().clear();
+        inner.push_frame();
+        Ok(())
+    }
+
+    /// Finish the recording, resolving once the file is written.
+    pub fn stop_future(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn Error>>> + 'static>> {
+        let inner = self.inner.clone();
+        let pipeline = match inner.pipeline.take() {
+            Some(pipeline) => pipeline,
+            None => return Box::pin(future::ready(Err("Recording not started".into()))),
+        };
+        inner.disconnect();
+
+        let bus = pipeline.bus().unwrap();
+        let (tx, rx) = oneshot::channel();
+        let mut tx = Some(tx);
+        let watch = bus.add_watch_local(move |_, msg| {
+            let res = match msg.view() {
+                gst::MessageView::Eos(_) => Ok(()),
+                gst::MessageView::Error(err) => Err(err.error()),
+                _ => return glib::Continue(true),
+            };
+            if let Some(tx) = tx.take() {
+                let _ = tx.send(res);
+            }
+            glib::Continue(false)
+        });
+
+        let resumed = if inner.paused.replace(false) {
+            pipeline.set_state(gst::State::Playing).map(|_| ())
+        } else {
+            Ok(())
+        };
+        for src in [inner.video_src.take(), inner.audio_src.take()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = src.end_of_stream();
+        }
+
+        Box::pin(async move {
+            let res: Result<(), Box<dyn Error>> = match (watch, resumed) {
+                (Err(e), _) => Err(e.into()),
+                (Ok(watch), Err(e)) => {
+                    watch.remove();
+                    Err(e.into())
+                }
+                (Ok(watch), Ok(())) => {
+                    let timeout = glib::timeout_future(Duration::from_secs(5));
+                    match future::select(rx, timeout).await {
+                        future::Either::Left((Ok(Ok(())), _)) => Ok(()),
+                        future::Either::Left((Ok(Err(e)), _)) => Err(e.into()),
+                        future::Either::Left((Err(_), _)) => Err("Recording bus is gone".into()),
+                        future::Either::Right(_) => {
+                            watch.remove();
+                            Err("Timeout while finishing the recording".into())
+                        }
+                    }
+                }
+            };
+            pipeline.set_state(gst::State::Null)?;
+            inner.video_size.set(None);
+            inner.last_frame.set(None);
+            res
+        })
+    }
+
+    pub fn stop_async<P: FnOnce(Result<(), Box<dyn Error>>) + 'static>(&self, callback: P) {
+        let future = self.stop_future();
+        glib::MainContext::default().spawn_local(async move {
+            callback(future.await);
+        });
+    }
+}
@@ -2,9 +2,29 @@
 #version 130
 
 uniform sampler2D tex_unit;
+// the chroma plane, when tex_unit holds the luma plane
+uniform sampler2D tex_uv_unit;
+uniform bool yuv;
+// 0: opaque, 1: straight alpha, 2: premultiplied alpha
+uniform int alpha_mode;
 in  mediump vec2 ex_tex_coord;
 out mediump vec4 out_frag_color;
 
 void main(void) {
-     out_frag_color = texture(tex_unit, ex_tex_coord);
+     vec4 color = texture(tex_unit, ex_tex_coord);
+     if (yuv) {
+          // BT.709, limited range
+          float y = 1.164 * (color.r - 0.0627);
+          vec2 uv = texture(tex_uv_unit, ex_tex_coord).rg - 0.5;
+          color = vec4(y + 1.793 * uv.y,
+                       y - 0.213 * uv.x - 0.533 * uv.y,
+                       y + 2.112 * uv.x,
+                       1.0);
+     }
+     if (alpha_mode == 0) {
+          color.a = 1.0;
+     } else if (alpha_mode == 1) {
+          color.rgb *= color.a;
+     }
+     out_frag_color = color;
 }
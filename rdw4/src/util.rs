@@ -40,3 +40,24 @@ pub(crate) unsafe fn compile_gl_prog(vs: &CStr, fs: &CStr) -> Result<GLuint, Str
     gl::DeleteShader(fs);
     Ok(prog)
 }
+
+pub(crate) unsafe fn uniform_location(prog: GLuint, name: &str) -> GLint {
+    let name = CString::new(name).unwrap();
+    gl::GetUniformLocation(prog, name.as_ptr())
+}
+
+// wait on the CPU for a sync-fd to signal, and close it
+#[cfg(unix)]
+pub(crate) fn wait_fence(fd: std::os::unix::io::RawFd) {
+    let mut pfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe {
+        if libc::poll(&mut pfd, 1, 1000) <= 0 {
+            log::warn!("Timeout waiting for fence");
+        }
+        libc::close(fd);
+    }
+}